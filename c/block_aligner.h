@@ -139,6 +139,8 @@ extern "C" {
 
 extern const struct NucMatrix NW1;
 
+extern const struct NucMatrix NUC_IDENTITY;
+
 extern const struct AAMatrix BLOSUM45;
 
 extern const struct AAMatrix BLOSUM50;
@@ -159,6 +161,8 @@ extern const struct AAMatrix PAM200;
 
 extern const struct AAMatrix PAM250;
 
+extern const struct AAMatrix AA_IDENTITY;
+
 extern const struct ByteMatrix BYTES1;
 
 /**
@@ -561,6 +565,31 @@ void block_cigar_eq_aa_trace_xdrop(BlockHandle b,
  */
 void block_free_aa_trace_xdrop(BlockHandle b);
 
+/**
+ * Globally align two amino acid strings with traceback, without creating any handles.
+ *
+ * The score is stored in `score` and the CIGAR string (with `=` and `X`) is stored in
+ * `cigar` as a null-terminated string, which must be freed with `block_free_cigar_str`.
+ * Returns false without storing anything if the CPU does not support the SIMD instruction set
+ * or if the arguments are invalid: null pointers, gap costs that are not negative or where gap
+ * open does not cost more than gap extend, block sizes that are not powers of two between
+ * 1 and 2^15, or bytes that are not letters.
+ */
+bool block_align_str_aa(const uint8_t *q,
+                        uintptr_t q_len,
+                        const uint8_t *r,
+                        uintptr_t r_len,
+                        const struct AAMatrix *m,
+                        struct Gaps g,
+                        struct SizeRange s,
+                        int32_t *score,
+                        char **cigar);
+
+/**
+ * Frees a CIGAR string from `block_align_str_aa`.
+ */
+void block_free_cigar_str(char *cigar);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif // __cplusplus
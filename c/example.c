@@ -118,8 +118,32 @@ void example3(void) {
     block_free_aaprofile(b);
 }
 
+void example4(void) {
+    // global seq-seq alignment with traceback, without handles
+    const char* a_str = "AAAAAAAA";
+    const char* b_str = "AARAAAA";
+    SizeRange range = {.min = 32, .max = 32};
+    Gaps gaps = {.open = -11, .extend = -1};
+
+    int32_t score;
+    char* cigar;
+    if (!block_align_str_aa((const uint8_t*)a_str, strlen(a_str), (const uint8_t*)b_str, strlen(b_str), &BLOSUM62, gaps, range, &score, &cigar)) {
+        printf("alignment failed\n");
+        return;
+    }
+
+    printf("a: %s\nb: %s\nscore: %d\ncigar: %s\n",
+            a_str,
+            b_str,
+            score,
+            cigar);
+
+    block_free_cigar_str(cigar);
+}
+
 int main() {
     example1();
     example2();
     example3();
+    example4();
 }
@@ -0,0 +1,94 @@
+#[cfg(not(feature = "simd_avx2"))]
+fn main() {}
+
+#[cfg(feature = "simd_avx2")]
+fn get_data(file_name: &str) -> Vec<(Vec<u8>, Vec<u8>, i32)> {
+    use parasailors::{Matrix, *};
+
+    use block_aligner::scan_block::*;
+    use block_aligner::scores::*;
+
+    use std::fs::File;
+    use std::io::{BufRead, BufReader};
+
+    let reader = BufReader::new(File::open(file_name).unwrap());
+    let all_lines = reader.lines().collect::<Vec<_>>();
+    let mut res = vec![];
+
+    for lines in all_lines.chunks(2) {
+        let r = lines[0].as_ref().unwrap().to_ascii_uppercase().into_bytes();
+        let q = lines[1].as_ref().unwrap().to_ascii_uppercase().into_bytes();
+
+        let correct_score = if r.len().max(q.len()) < 15000 {
+            // parasail
+            let matrix = Matrix::create("ACGNT", 2, -4);
+            let profile = parasailors::Profile::new(&q, &matrix);
+            global_alignment_score(&profile, &r, 6, 2)
+        } else {
+            // parasail is not accurate enough, so use block aligner with large fixed block size
+            let len = 8192;
+            let r_padded = PaddedBytes::from_bytes::<NucMatrix>(&r, len);
+            let q_padded = PaddedBytes::from_bytes::<NucMatrix>(&q, len);
+            let run_gaps = Gaps { open: -6, extend: -2 };
+            let matrix = NucMatrix::new_simple(2, -4);
+            let mut block_aligner = Block::<false, false>::new(q.len(), r.len(), len);
+            block_aligner.align(&q_padded, &r_padded, &matrix, run_gaps, len..=len, 0);
+            block_aligner.res().score
+        };
+
+        res.push((q, r, correct_score));
+    }
+
+    res
+}
+
+#[cfg(feature = "simd_avx2")]
+fn test(data: &[(Vec<u8>, Vec<u8>, i32)], min_size: usize, max_size: usize) -> (f64, f64) {
+    use block_aligner::scan_block::*;
+    use block_aligner::scores::*;
+
+    use std::time::{Instant, Duration};
+
+    let run_gaps = Gaps { open: -6, extend: -2 };
+    let matrix = NucMatrix::new_simple(2, -4);
+    let padded = data
+        .iter()
+        .map(|(q, r, s)| (PaddedBytes::from_bytes::<NucMatrix>(q, max_size), PaddedBytes::from_bytes::<NucMatrix>(r, max_size), *s))
+        .collect::<Vec<_>>();
+
+    let mut correct = 0usize;
+    let mut total_time = Duration::ZERO;
+
+    for (q, r, correct_score) in &padded {
+        let mut block_aligner = Block::<false, false>::new(q.len(), r.len(), max_size);
+        let start = Instant::now();
+        block_aligner.align(q, r, &matrix, run_gaps, min_size..=max_size, 0);
+        total_time += start.elapsed();
+        if block_aligner.res().score == *correct_score {
+            correct += 1;
+        }
+    }
+
+    (total_time.as_secs_f64(), (correct as f64) / (data.len() as f64))
+}
+
+#[cfg(feature = "simd_avx2")]
+fn main() {
+    let paths = ["data/real.illumina.b10M.txt", "data/real.ont.b10M.txt", "data/seq_pairs.10kbps.5000.txt"];
+    let names = ["illumina", "nanopore 1kbp", "nanopore <10kbp"];
+    let sizes = [(32, 32), (64, 64), (128, 128), (256, 256), (512, 512), (1024, 1024), (32, 256), (32, 1024)];
+
+    println!("# time is only for the alignment, without allocation and padding");
+    println!("dataset, min size, max size, time (s), fraction correct");
+
+    for (path, name) in paths.iter().zip(&names) {
+        let data = get_data(path);
+
+        for &(min_size, max_size) in &sizes {
+            let (time, frac_correct) = test(&data, min_size, max_size);
+            println!("{}, {}, {}, {}, {}", name, min_size, max_size, time, frac_correct);
+        }
+    }
+
+    println!("# Done!");
+}
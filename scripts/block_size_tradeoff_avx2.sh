@@ -0,0 +1 @@
+cargo run --example block_size_tradeoff --release --features simd_avx2 -- "$@"
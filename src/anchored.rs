@@ -0,0 +1,181 @@
+//! Global alignment that is forced through anchor positions, and X-drop extension from seeds.
+
+use crate::scan_block::*;
+use crate::scores::*;
+use crate::cigar::*;
+
+use core::ops::RangeInclusive;
+use alloc::vec::Vec;
+
+/// Globally align two sequences, requiring that each anchor `(query_idx, reference_idx)`
+/// pair of bytes is aligned to each other.
+///
+/// Anchors are 0-based positions in the original bytes, for example, equivalent residues from
+/// a structural superposition. The sequences are split at the anchors and each segment between two
+/// anchors is globally aligned with `Block::align`, so the alignment passes through every anchor.
+///
+/// Returns the total score and the CIGAR string with `=` and `X` operations, or `None` if the anchors
+/// cannot all be satisfied (they are out of bounds or they are not strictly increasing
+/// in both the query and the reference).
+///
+/// Unlike `Block::align`, this takes the original bytes.
+pub fn align_anchored<M: Matrix>(query: &[u8], reference: &[u8], matrix: &M, gaps: Gaps, anchors: &[(usize, usize)], size: RangeInclusive<usize>) -> Option<(i32, Cigar)> {
+    let mut prev: Option<(usize, usize)> = None;
+    for &(i, j) in anchors {
+        if i >= query.len() || j >= reference.len() {
+            return None;
+        }
+        if let Some((prev_i, prev_j)) = prev {
+            if i <= prev_i || j <= prev_j {
+                return None;
+            }
+        }
+        prev = Some((i, j));
+    }
+
+    let max_size = *size.end();
+    let mut block = Block::<true, false>::new(query.len(), reference.len(), max_size);
+    let mut seg_cigar = Cigar::new(query.len(), reference.len());
+    let mut ops: Vec<OpLen> = Vec::new();
+    let mut score = 0i32;
+
+    let mut start_i = 0;
+    let mut start_j = 0;
+
+    // the segment after the last anchor is aligned with an anchor past the ends
+    for k in 0..=anchors.len() {
+        let (end_i, end_j) = if k < anchors.len() { anchors[k] } else { (query.len(), reference.len()) };
+        let q = &query[start_i..end_i];
+        let r = &reference[start_j..end_j];
+
+        if q.is_empty() || r.is_empty() {
+            if !q.is_empty() {
+                score += gap_cost(gaps, q.len());
+                push_op(&mut ops, OpLen { op: Operation::I, len: q.len() });
+            }
+            if !r.is_empty() {
+                score += gap_cost(gaps, r.len());
+                push_op(&mut ops, OpLen { op: Operation::D, len: r.len() });
+            }
+        } else {
+            let q_padded = PaddedBytes::from_bytes::<M>(q, max_size);
+            let r_padded = PaddedBytes::from_bytes::<M>(r, max_size);
+            block.align(&q_padded, &r_padded, matrix, gaps, size.clone(), 0);
+            let res = block.res();
+            score += res.score;
+            block.trace().cigar_eq(&q_padded, &r_padded, res.query_idx, res.reference_idx, &mut seg_cigar);
+            seg_cigar.to_vec().into_iter().for_each(|op_len| push_op(&mut ops, op_len));
+        }
+
+        if k < anchors.len() {
+            score += matrix.get(query[end_i], reference[end_j]) as i32;
+            let op = if M::convert_char(query[end_i]) == M::convert_char(reference[end_j]) { Operation::Eq } else { Operation::X };
+            push_op(&mut ops, OpLen { op, len: 1 });
+            start_i = end_i + 1;
+            start_j = end_j + 1;
+        }
+    }
+
+    Some((score, Cigar::from_op_lens(&ops, query.len(), reference.len())))
+}
+
+/// Extend a seed at `(seed_q, seed_r)` in both directions with X-drop alignment.
+///
+/// The suffixes `query[seed_q..]` and `reference[seed_r..]` are aligned with `Block::align`
+/// to extend right, and the reversed prefixes `query[..seed_q]` and `reference[..seed_r]`
+/// are aligned to extend left.
+/// Returns the results of the left and right extensions. The indices of the left result are
+/// the number of bytes before the seed, so the extended region of the query is
+/// `seed_q - left.query_idx..seed_q + right.query_idx`, and the total score is the sum of both scores.
+/// If a prefix or suffix is empty, then its result has a score of zero.
+///
+/// Unlike `Block::align`, this takes the original bytes. X-drop alignment with `ByteMatrix`
+/// is not supported.
+#[allow(clippy::too_many_arguments)]
+pub fn extend_seed<M: Matrix>(query: &[u8], reference: &[u8], seed_q: usize, seed_r: usize, matrix: &M, gaps: Gaps, size: RangeInclusive<usize>, x_drop: i32) -> (AlignResult, AlignResult) {
+    assert!(seed_q <= query.len() && seed_r <= reference.len(), "Seed must be within the sequences!");
+
+    let max_size = *size.end();
+    let mut block = Block::<false, true>::new(query.len(), reference.len(), max_size);
+    let empty = AlignResult { score: 0, query_idx: 0, reference_idx: 0 };
+
+    let left = if seed_q == 0 || seed_r == 0 {
+        empty
+    } else {
+        let mut q = PaddedBytes::new::<M>(seed_q, max_size);
+        let mut r = PaddedBytes::new::<M>(seed_r, max_size);
+        q.set_bytes_rev::<M>(&query[..seed_q], max_size);
+        r.set_bytes_rev::<M>(&reference[..seed_r], max_size);
+        block.align(&q, &r, matrix, gaps, size.clone(), x_drop);
+        block.res()
+    };
+
+    let right = if seed_q == query.len() || seed_r == reference.len() {
+        empty
+    } else {
+        let q = PaddedBytes::from_bytes::<M>(&query[seed_q..], max_size);
+        let r = PaddedBytes::from_bytes::<M>(&reference[seed_r..], max_size);
+        block.align(&q, &r, matrix, gaps, size, x_drop);
+        block.res()
+    };
+
+    (left, right)
+}
+
+fn gap_cost(gaps: Gaps, len: usize) -> i32 {
+    (gaps.open as i32) + (gaps.extend as i32) * ((len as i32) - 1)
+}
+
+fn push_op(ops: &mut Vec<OpLen>, op_len: OpLen) {
+    match ops.last_mut() {
+        Some(last) if last.op == op_len.op => last.len += op_len.len,
+        _ => ops.push(op_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align_anchored() {
+        let gaps = Gaps { open: -11, extend: -1 };
+        let q = b"AAAARRRRAAAA";
+        let r = b"AAAAAAAA";
+
+        let (score, cigar) = align_anchored(q, r, &BLOSUM62, gaps, &[], 16..=16).unwrap();
+        assert_eq!(score, 32 - 14);
+        assert_eq!(cigar.to_string(), "4=4I4=");
+
+        // force the second R to be aligned to the second A
+        let (score, cigar) = align_anchored(q, r, &BLOSUM62, gaps, &[(5, 1)], 16..=16).unwrap();
+        assert_eq!(cigar.to_string(), "1=4I3X4=");
+        assert_eq!(score, cigar.score(q, r, &BLOSUM62, gaps));
+
+        let (score, cigar) = align_anchored(q, r, &BLOSUM62, gaps, &[(0, 0), (11, 7)], 16..=16).unwrap();
+        assert_eq!(score, 32 - 14);
+        assert_eq!(cigar.to_string(), "4=4I4=");
+
+        assert!(align_anchored(q, r, &BLOSUM62, gaps, &[(3, 3), (2, 4)], 16..=16).is_none());
+        assert!(align_anchored(q, r, &BLOSUM62, gaps, &[(3, 8)], 16..=16).is_none());
+    }
+
+    #[test]
+    fn test_extend_seed() {
+        let gaps = Gaps { open: -11, extend: -1 };
+        let q = b"WWWWAAAAMMMMAAAAWWWW";
+        let r = b"CCCCCAAAAMMMMAAAACCCCC";
+
+        // the seed is the start of MMMM
+        let (left, right) = extend_seed(q, r, 8, 9, &BLOSUM62, gaps, 16..=16, 5);
+        assert_eq!(left, AlignResult { score: 16, query_idx: 4, reference_idx: 4 });
+        assert_eq!(right, AlignResult { score: 20 + 16, query_idx: 8, reference_idx: 8 });
+
+        let (left, right) = extend_seed(q, r, 0, 0, &BLOSUM62, gaps, 16..=16, 5);
+        assert_eq!(left, AlignResult { score: 0, query_idx: 0, reference_idx: 0 });
+        assert!(right.score > 0);
+
+        let (_, right) = extend_seed(q, r, q.len(), 9, &BLOSUM62, gaps, 16..=16, 5);
+        assert_eq!(right, AlignResult { score: 0, query_idx: 0, reference_idx: 0 });
+    }
+}
@@ -1,7 +1,7 @@
 #[cfg(target_arch = "x86")]
-use std::arch::x86::*;
+use core::arch::x86::*;
 #[cfg(target_arch = "x86_64")]
-use std::arch::x86_64::*;
+use core::arch::x86_64::*;
 
 pub type Simd = __m256i; // use for storing DP scores
 pub type HalfSimd = __m128i; // used for storing bytes (sequence or scoring matrix)
@@ -68,9 +68,9 @@ macro_rules! simd_extract_i16 {
         {
             debug_assert!($num < L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             _mm256_extract_epi16($a, $num as i32) as i16
         }
     };
@@ -83,9 +83,9 @@ macro_rules! simd_insert_i16 {
         {
             debug_assert!($num < L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             _mm256_insert_epi16($a, $v, $num as i32)
         }
     };
@@ -102,9 +102,9 @@ macro_rules! simd_sl_i16 {
         {
             debug_assert!(2 * $num <= L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             if $num == L / 2 {
                 _mm256_permute2x128_si256($a, $b, 0x03)
             } else {
@@ -121,9 +121,9 @@ macro_rules! simd_sr_i16 {
         {
             debug_assert!(2 * $num <= L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             if $num == L / 2 {
                 _mm256_permute2x128_si256($a, $b, 0x03)
             } else {
@@ -152,9 +152,9 @@ macro_rules! simd_sllz_i16 {
         {
             debug_assert!(2 * $num < L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             _mm256_slli_si256($a, ($num * 2) as i32)
         }
     };
@@ -198,9 +198,9 @@ macro_rules! simd_prefix_hadd_i16 {
         {
             debug_assert!(2 * $num <= L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             let mut v = _mm256_subs_epi16($a, _mm256_set1_epi16(ZERO));
             if $num > 4 {
                 v = _mm256_adds_epi16(v, _mm256_srli_si256(v, 8));
@@ -223,9 +223,9 @@ macro_rules! simd_prefix_hmax_i16 {
         {
             debug_assert!(2 * $num <= L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             let mut v = $a;
             if $num > 4 {
                 v = _mm256_max_epi16(v, _mm256_srli_si256(v, 8));
@@ -248,9 +248,9 @@ macro_rules! simd_suffix_hmax_i16 {
         {
             debug_assert!(2 * $num <= L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             let mut v = $a;
             if $num > 4 {
                 v = _mm256_max_epi16(v, _mm256_slli_si256(v, 8));
@@ -402,15 +402,16 @@ macro_rules! halfsimd_sr_i8 {
         {
             debug_assert!($num <= L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             _mm_alignr_epi8($a, $b, $num as i32)
         }
     };
 }
 
 #[target_feature(enable = "avx2")]
+#[cfg(not(feature = "no_std"))]
 #[allow(dead_code)]
 pub unsafe fn simd_dbg_i16(v: Simd) {
     #[repr(align(32))]
@@ -426,6 +427,7 @@ pub unsafe fn simd_dbg_i16(v: Simd) {
 }
 
 #[target_feature(enable = "avx2")]
+#[cfg(not(feature = "no_std"))]
 #[allow(dead_code)]
 pub unsafe fn halfsimd_dbg_i8(v: HalfSimd) {
     #[repr(align(16))]
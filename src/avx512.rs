@@ -0,0 +1,548 @@
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+pub type Simd = __m512i; // use for storing DP scores
+pub type HalfSimd = __m256i; // used for storing bytes (sequence or scoring matrix)
+pub type LutSimd = __m128i; // used for storing a row in a scoring matrix (always 128 bits)
+pub type TraceType = i64;
+/// Number of 16-bit lanes in a SIMD vector.
+pub const L: usize = 32;
+pub const L_BYTES: usize = L * 2;
+pub const HALFSIMD_MUL: usize = 1;
+// using min = 0 is faster, but restricts range of scores (and restricts the max block size)
+pub const ZERO: i16 = 1 << 14;
+pub const MIN: i16 = 0;
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn store_trace(ptr: *mut TraceType, trace: TraceType) { *ptr = trace; }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_adds_i16(a: Simd, b: Simd) -> Simd { _mm512_adds_epi16(a, b) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_subs_i16(a: Simd, b: Simd) -> Simd { _mm512_subs_epi16(a, b) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_max_i16(a: Simd, b: Simd) -> Simd { _mm512_max_epi16(a, b) }
+
+// comparisons produce mask registers, so they are expanded back into vectors
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_cmpeq_i16(a: Simd, b: Simd) -> Simd { _mm512_movm_epi16(_mm512_cmpeq_epi16_mask(a, b)) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_cmpgt_i16(a: Simd, b: Simd) -> Simd { _mm512_movm_epi16(_mm512_cmpgt_epi16_mask(a, b)) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_blend_i8(a: Simd, b: Simd, mask: Simd) -> Simd { _mm512_mask_blend_epi8(_mm512_movepi8_mask(mask), a, b) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_load(ptr: *const Simd) -> Simd { _mm512_load_si512(ptr) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_loadu(ptr: *const Simd) -> Simd { _mm512_loadu_si512(ptr) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_store(ptr: *mut Simd, a: Simd) { _mm512_store_si512(ptr, a) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_set1_i16(v: i16) -> Simd { _mm512_set1_epi16(v) }
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! simd_extract_i16 {
+    ($a:expr, $num:expr) => {
+        {
+            debug_assert!($num < L);
+            #[cfg(target_arch = "x86")]
+            use core::arch::x86::*;
+            #[cfg(target_arch = "x86_64")]
+            use core::arch::x86_64::*;
+            _mm_extract_epi16(_mm512_extracti32x4_epi32($a, ($num / 8) as i32), ($num % 8) as i32) as i16
+        }
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! simd_insert_i16 {
+    ($a:expr, $v:expr, $num:expr) => {
+        {
+            debug_assert!($num < L);
+            #[cfg(target_arch = "x86")]
+            use core::arch::x86::*;
+            #[cfg(target_arch = "x86_64")]
+            use core::arch::x86_64::*;
+            _mm512_mask_set1_epi16($a, 1u32 << $num, $v)
+        }
+    };
+}
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_movemask_i8(a: Simd) -> u64 { _mm512_movepi8_mask(a) }
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! simd_sl_i16 {
+    ($a:expr, $b:expr, $num:expr) => {
+        {
+            debug_assert!(4 * $num <= L);
+            #[cfg(target_arch = "x86")]
+            use core::arch::x86::*;
+            #[cfg(target_arch = "x86_64")]
+            use core::arch::x86_64::*;
+            if $num == L / 4 {
+                _mm512_alignr_epi32($a, $b, 12)
+            } else {
+                _mm512_alignr_epi8($a, _mm512_alignr_epi32($a, $b, 12), (16 - (2 * $num)) as i32)
+            }
+        }
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! simd_sr_i16 {
+    ($a:expr, $b:expr, $num:expr) => {
+        {
+            debug_assert!(4 * $num <= L);
+            #[cfg(target_arch = "x86")]
+            use core::arch::x86::*;
+            #[cfg(target_arch = "x86_64")]
+            use core::arch::x86_64::*;
+            if $num == L / 4 {
+                _mm512_alignr_epi32($a, $b, 4)
+            } else {
+                _mm512_alignr_epi8(_mm512_alignr_epi32($a, $b, 4), $b, (2 * $num) as i32)
+            }
+        }
+    };
+}
+
+// hardcoded to STEP = 8
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_step(a: Simd, b: Simd) -> Simd {
+    _mm512_alignr_epi32(a, b, 4)
+}
+
+// shift in zeros within each 128-bit lane
+macro_rules! simd_sllz_i16 {
+    ($a:expr, $num:expr) => {
+        {
+            debug_assert!(4 * $num < L);
+            #[cfg(target_arch = "x86")]
+            use core::arch::x86::*;
+            #[cfg(target_arch = "x86_64")]
+            use core::arch::x86_64::*;
+            _mm512_bslli_epi128($a, ($num * 2) as i32)
+        }
+    };
+}
+
+// shift in zeros across 128-bit lanes, by a multiple of 8 elements
+macro_rules! simd_sllz_lanes_i16 {
+    ($a:expr, $num:expr) => {
+        {
+            debug_assert!($num % 8 == 0 && $num < L);
+            #[cfg(target_arch = "x86")]
+            use core::arch::x86::*;
+            #[cfg(target_arch = "x86_64")]
+            use core::arch::x86_64::*;
+            _mm512_alignr_epi32($a, _mm512_setzero_si512(), (16 - $num / 2) as i32)
+        }
+    };
+}
+
+// broadcast last 16-bit element to the whole vector
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_broadcasthi_i16(v: Simd) -> Simd {
+    _mm512_permutexvar_epi16(_mm512_set1_epi16((L - 1) as i16), v)
+}
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_slow_extract_i16(v: Simd, i: usize) -> i16 {
+    debug_assert!(i < L);
+
+    #[repr(align(64))]
+    struct A([i16; L]);
+
+    let mut a = A([0i16; L]);
+    simd_store(a.0.as_mut_ptr() as *mut Simd, v);
+    *a.0.as_ptr().add(i)
+}
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_hmax_i16(v: Simd) -> i16 {
+    let mut v2 = _mm512_max_epi16(v, _mm512_bsrli_epi128(v, 2));
+    v2 = _mm512_max_epi16(v2, _mm512_bsrli_epi128(v2, 4));
+    v2 = _mm512_max_epi16(v2, _mm512_bsrli_epi128(v2, 8));
+    v2 = _mm512_max_epi16(v2, _mm512_alignr_epi32(v2, v2, 4));
+    v2 = _mm512_max_epi16(v2, _mm512_alignr_epi32(v2, v2, 8));
+    simd_extract_i16!(v2, 0)
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! simd_prefix_hadd_i16 {
+    ($a:expr, $num:expr) => {
+        {
+            debug_assert!(4 * $num <= L);
+            #[cfg(target_arch = "x86")]
+            use core::arch::x86::*;
+            #[cfg(target_arch = "x86_64")]
+            use core::arch::x86_64::*;
+            let mut v = _mm512_subs_epi16($a, _mm512_set1_epi16(ZERO));
+            if $num > 4 {
+                v = _mm512_adds_epi16(v, _mm512_bsrli_epi128(v, 8));
+            }
+            if $num > 2 {
+                v = _mm512_adds_epi16(v, _mm512_bsrli_epi128(v, 4));
+            }
+            if $num > 1 {
+                v = _mm512_adds_epi16(v, _mm512_bsrli_epi128(v, 2));
+            }
+            simd_extract_i16!(v, 0)
+        }
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! simd_prefix_hmax_i16 {
+    ($a:expr, $num:expr) => {
+        {
+            debug_assert!(4 * $num <= L);
+            #[cfg(target_arch = "x86")]
+            use core::arch::x86::*;
+            #[cfg(target_arch = "x86_64")]
+            use core::arch::x86_64::*;
+            let mut v = $a;
+            if $num > 4 {
+                v = _mm512_max_epi16(v, _mm512_bsrli_epi128(v, 8));
+            }
+            if $num > 2 {
+                v = _mm512_max_epi16(v, _mm512_bsrli_epi128(v, 4));
+            }
+            if $num > 1 {
+                v = _mm512_max_epi16(v, _mm512_bsrli_epi128(v, 2));
+            }
+            simd_extract_i16!(v, 0)
+        }
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! simd_suffix_hmax_i16 {
+    ($a:expr, $num:expr) => {
+        {
+            debug_assert!(4 * $num <= L);
+            #[cfg(target_arch = "x86")]
+            use core::arch::x86::*;
+            #[cfg(target_arch = "x86_64")]
+            use core::arch::x86_64::*;
+            let mut v = $a;
+            if $num > 4 {
+                v = _mm512_max_epi16(v, _mm512_bslli_epi128(v, 8));
+            }
+            if $num > 2 {
+                v = _mm512_max_epi16(v, _mm512_bslli_epi128(v, 4));
+            }
+            if $num > 1 {
+                v = _mm512_max_epi16(v, _mm512_bslli_epi128(v, 2));
+            }
+            simd_extract_i16!(v, 31)
+        }
+    };
+}
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn simd_hargmax_i16(v: Simd, max: i16) -> usize {
+    _mm512_cmpeq_epi16_mask(v, _mm512_set1_epi16(max)).trailing_zeros() as usize
+}
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+#[allow(non_snake_case)]
+#[allow(dead_code)]
+pub unsafe fn simd_naive_prefix_scan_i16(R_max: Simd, gap_cost: Simd, _gap_cost_lane: PrefixScanConsts) -> Simd {
+    let mut curr = R_max;
+
+    for _i in 0..(L - 1) {
+        let prev = curr;
+        curr = simd_sl_i16!(curr, _mm512_setzero_si512(), 1);
+        curr = _mm512_adds_epi16(curr, gap_cost);
+        curr = _mm512_max_epi16(curr, prev);
+    }
+
+    curr
+}
+
+pub type PrefixScanConsts = Simd;
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn get_prefix_scan_consts(gap: Simd) -> (Simd, PrefixScanConsts) {
+    let mut shift1 = simd_sllz_i16!(gap, 1);
+    shift1 = _mm512_adds_epi16(shift1, gap);
+    let mut shift2 = simd_sllz_i16!(shift1, 2);
+    shift2 = _mm512_adds_epi16(shift2, shift1);
+    let mut shift4 = simd_sllz_i16!(shift2, 4);
+    shift4 = _mm512_adds_epi16(shift4, shift2);
+    // add the gap costs of the previous 128-bit lanes
+    let mut lanes = simd_sllz_lanes_i16!(_mm512_slli_epi16(gap, 3), 8);
+    lanes = _mm512_adds_epi16(lanes, simd_sllz_lanes_i16!(lanes, 8));
+    lanes = _mm512_adds_epi16(lanes, simd_sllz_lanes_i16!(lanes, 16));
+
+    (_mm512_adds_epi16(shift4, lanes), shift4)
+}
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+#[allow(non_snake_case)]
+pub unsafe fn simd_prefix_scan_i16(R_max: Simd, gap_cost: Simd, gap_cost_lane: PrefixScanConsts) -> Simd {
+    // Optimized prefix add and max for every eight elements, like the AVX2 version
+    // Note: relies on min score = 0 for speed!
+    let mut shift1 = simd_sllz_i16!(R_max, 1);
+    shift1 = _mm512_adds_epi16(shift1, gap_cost);
+    shift1 = _mm512_max_epi16(R_max, shift1);
+    let mut shift2 = simd_sllz_i16!(shift1, 2);
+    shift2 = _mm512_adds_epi16(shift2, _mm512_slli_epi16(gap_cost, 1));
+    shift2 = _mm512_max_epi16(shift1, shift2);
+    let mut shift4 = simd_sllz_i16!(shift2, 4);
+    shift4 = _mm512_adds_epi16(shift4, _mm512_slli_epi16(gap_cost, 2));
+    shift4 = _mm512_max_epi16(shift2, shift4);
+
+    // Broadcast the last element of each 128-bit lane to the next lane, then
+    // prefix scan across the four lanes to correct the upper lanes
+    let mut last = _mm512_shufflehi_epi16(shift4, 0b11111111);
+    last = _mm512_shuffle_epi32(last, _MM_PERM_DCDC);
+    let mut correct1 = simd_sllz_lanes_i16!(last, 8);
+    let mut correct2 = simd_sllz_lanes_i16!(correct1, 8);
+    correct2 = _mm512_adds_epi16(correct2, _mm512_slli_epi16(gap_cost, 3));
+    correct1 = _mm512_max_epi16(correct1, correct2);
+    let mut correct3 = simd_sllz_lanes_i16!(correct1, 16);
+    correct3 = _mm512_adds_epi16(correct3, _mm512_slli_epi16(gap_cost, 4));
+    correct1 = _mm512_max_epi16(correct1, correct3);
+    correct1 = _mm512_adds_epi16(correct1, gap_cost_lane);
+    _mm512_max_epi16(shift4, correct1)
+}
+
+// lookup two 128-bit tables
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn halfsimd_lookup2_i16(lut1: LutSimd, lut2: LutSimd, v: HalfSimd) -> Simd {
+    let a = _mm256_shuffle_epi8(_mm256_broadcastsi128_si256(lut1), v);
+    let b = _mm256_shuffle_epi8(_mm256_broadcastsi128_si256(lut2), v);
+    // only the most significant bit of each byte matters for blendv
+    let mask = _mm256_slli_epi16(v, 3);
+    let c = _mm256_blendv_epi8(a, b, mask);
+    _mm512_cvtepi8_epi16(c)
+}
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn halfsimd_lookup1_i16(lut: LutSimd, v: HalfSimd) -> Simd {
+    _mm512_cvtepi8_epi16(_mm256_shuffle_epi8(_mm256_broadcastsi128_si256(lut), v))
+}
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn halfsimd_lookup_bytes_i16(match_scores: HalfSimd, mismatch_scores: HalfSimd, a: HalfSimd, b: HalfSimd) -> Simd {
+    let mask = _mm256_cmpeq_epi8(a, b);
+    let c = _mm256_blendv_epi8(mismatch_scores, match_scores, mask);
+    _mm512_cvtepi8_epi16(c)
+}
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn halfsimd_load(ptr: *const HalfSimd) -> HalfSimd { _mm256_load_si256(ptr) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn halfsimd_loadu(ptr: *const HalfSimd) -> HalfSimd { _mm256_loadu_si256(ptr) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn lutsimd_load(ptr: *const LutSimd) -> LutSimd { _mm_load_si128(ptr) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn lutsimd_loadu(ptr: *const LutSimd) -> LutSimd { _mm_loadu_si128(ptr) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn halfsimd_store(ptr: *mut HalfSimd, a: HalfSimd) { _mm256_store_si256(ptr, a) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn halfsimd_sub_i8(a: HalfSimd, b: HalfSimd) -> HalfSimd { _mm256_sub_epi8(a, b) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn halfsimd_set1_i8(v: i8) -> HalfSimd { _mm256_set1_epi8(v) }
+
+#[target_feature(enable = "avx512bw")]
+#[inline]
+pub unsafe fn halfsimd_get_idx(i: usize) -> usize { i }
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! halfsimd_sr_i8 {
+    ($a:expr, $b:expr, $num:expr) => {
+        {
+            debug_assert!(2 * $num <= L);
+            #[cfg(target_arch = "x86")]
+            use core::arch::x86::*;
+            #[cfg(target_arch = "x86_64")]
+            use core::arch::x86_64::*;
+            _mm256_alignr_epi8(_mm256_permute2x128_si256($a, $b, 0x03), $b, $num as i32)
+        }
+    };
+}
+
+#[target_feature(enable = "avx512bw")]
+#[cfg(not(feature = "no_std"))]
+#[allow(dead_code)]
+pub unsafe fn simd_dbg_i16(v: Simd) {
+    #[repr(align(64))]
+    struct A([i16; L]);
+
+    let mut a = A([0i16; L]);
+    simd_store(a.0.as_mut_ptr() as *mut Simd, v);
+
+    for i in (0..a.0.len()).rev() {
+        print!("{:6} ", a.0[i]);
+    }
+    println!();
+}
+
+#[target_feature(enable = "avx512bw")]
+#[cfg(not(feature = "no_std"))]
+#[allow(dead_code)]
+pub unsafe fn halfsimd_dbg_i8(v: HalfSimd) {
+    #[repr(align(32))]
+    struct A([i8; L]);
+
+    let mut a = A([0i8; L]);
+    halfsimd_store(a.0.as_mut_ptr() as *mut HalfSimd, v);
+
+    for i in (0..a.0.len()).rev() {
+        print!("{:3} ", a.0[i]);
+    }
+    println!();
+}
+
+#[target_feature(enable = "avx512bw")]
+#[allow(dead_code)]
+pub unsafe fn simd_assert_vec_eq(a: Simd, b: [i16; L]) {
+    #[repr(align(64))]
+    struct A([i16; L]);
+
+    let mut arr = A([0i16; L]);
+    simd_store(arr.0.as_mut_ptr() as *mut Simd, a);
+    assert_eq!(arr.0, b);
+}
+
+#[target_feature(enable = "avx512bw")]
+#[allow(dead_code)]
+pub unsafe fn halfsimd_assert_vec_eq(a: HalfSimd, b: [i8; L]) {
+    #[repr(align(32))]
+    struct A([i8; L]);
+
+    let mut arr = A([0i8; L]);
+    halfsimd_store(arr.0.as_mut_ptr() as *mut HalfSimd, a);
+    assert_eq!(arr.0, b);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_scan() {
+        #[target_feature(enable = "avx512bw")]
+        unsafe fn inner() {
+            #[repr(align(64))]
+            struct A([i16; L]);
+
+            let vec = A([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 15, 12, 13, 14, 11, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 20, 13, 14, 11]);
+            let gap = simd_set1_i16(0);
+            let (_, consts) = get_prefix_scan_consts(gap);
+            let res = simd_prefix_scan_i16(simd_load(vec.0.as_ptr() as *const Simd), gap, consts);
+            simd_assert_vec_eq(res, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 20, 20, 20, 20]);
+
+            let vec = A([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 15, 12, 13, 14, 11, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 20, 13, 14, 11]);
+            let gap = simd_set1_i16(-1);
+            let (_, consts) = get_prefix_scan_consts(gap);
+            let res = simd_prefix_scan_i16(simd_load(vec.0.as_ptr() as *const Simd), gap, consts);
+            simd_assert_vec_eq(res, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 15, 14, 13, 14, 13, 12, 11, 10, 9, 8, 7, 6, 7, 8, 9, 10, 11, 20, 19, 18, 17]);
+
+            let (gap_all, _) = get_prefix_scan_consts(gap);
+            let mut expected = [0i16; L];
+            for i in 0..L {
+                expected[i] = -(i as i16) - 1;
+            }
+            simd_assert_vec_eq(gap_all, expected);
+        }
+        unsafe { inner(); }
+    }
+
+    #[test]
+    fn test_shift() {
+        #[target_feature(enable = "avx512bw")]
+        unsafe fn inner() {
+            #[repr(align(64))]
+            struct A([i16; L]);
+
+            let mut a = A([0i16; L]);
+            let mut b = A([0i16; L]);
+            for i in 0..L {
+                a.0[i] = i as i16;
+                b.0[i] = (L + i) as i16;
+            }
+            let a = simd_load(a.0.as_ptr() as *const Simd);
+            let b = simd_load(b.0.as_ptr() as *const Simd);
+
+            let mut expected = [0i16; L];
+            for i in 0..L {
+                expected[i] = if i == 0 { (2 * L - 1) as i16 } else { (i - 1) as i16 };
+            }
+            simd_assert_vec_eq(simd_sl_i16!(a, b, 1), expected);
+
+            for i in 0..L {
+                expected[i] = if i < L - 8 { (L + i + 8) as i16 } else { (i + 8 - L) as i16 };
+            }
+            simd_assert_vec_eq(simd_step(a, b), expected);
+
+            for i in 0..L {
+                expected[i] = if i < L - 1 { (L + i + 1) as i16 } else { 0 };
+            }
+            simd_assert_vec_eq(simd_sr_i16!(a, b, 1), expected);
+
+            assert_eq!(simd_extract_i16!(b, L - 1), (2 * L - 1) as i16);
+            assert_eq!(simd_extract_i16!(simd_insert_i16!(a, 100, 20), 20), 100);
+            assert_eq!(simd_hmax_i16(a), (L - 1) as i16);
+            assert_eq!(simd_extract_i16!(simd_broadcasthi_i16(a), 3), (L - 1) as i16);
+        }
+        unsafe { inner(); }
+    }
+}
@@ -0,0 +1,50 @@
+//! Multi-threaded alignment of many pairs of sequences with rayon.
+
+use crate::scan_block::*;
+use crate::scores::*;
+
+use rayon::prelude::*;
+
+use std::ops::RangeInclusive;
+
+/// Align many pairs of sequences in parallel and return the result for each pair, in order.
+///
+/// Each worker thread allocates its own score-only block aligner and reuses it for all of
+/// the pairs it aligns, so no scratch space is shared between threads.
+/// See `Block::align` for the other parameters.
+pub fn align_batch<M: Matrix + Sync, const X_DROP: bool>(pairs: &[(PaddedBytes, PaddedBytes)], matrix: &M, gaps: Gaps, size: RangeInclusive<usize>, x_drop: i32) -> Vec<AlignResult> {
+    let max_size = *size.end();
+
+    pairs
+        .par_iter()
+        .map_init(|| Block::<false, X_DROP>::new(0, 0, max_size), |block, (q, r)| {
+            block.reset(q.len(), r.len(), max_size);
+            block.align(q, r, matrix, gaps, size.clone(), x_drop);
+            block.res()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align_batch() {
+        let gaps = Gaps { open: -11, extend: -1 };
+        let seqs: [&[u8]; 4] = [b"AAAA", b"AARA", b"AAAAAAAAAARAAAAAAAAAAA", b"ACDEFGHIKLMNPQRSTVWY"];
+        let pairs = seqs
+            .iter()
+            .flat_map(|&q| seqs.iter().map(move |&r| (PaddedBytes::from_bytes::<AAMatrix>(q, 32), PaddedBytes::from_bytes::<AAMatrix>(r, 32))))
+            .collect::<Vec<_>>();
+
+        let res = align_batch::<_, false>(&pairs, &BLOSUM62, gaps, 32..=32, 0);
+        assert_eq!(res.len(), pairs.len());
+
+        for ((q, r), res) in pairs.iter().zip(&res) {
+            let mut block = Block::<false, false>::new(q.len(), r.len(), 32);
+            block.align(q, r, &BLOSUM62, gaps, 32..=32, 0);
+            assert_eq!(*res, block.res());
+        }
+    }
+}
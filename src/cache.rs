@@ -0,0 +1,218 @@
+//! Caching of alignment results for workloads that align the same pairs of sequences many times.
+
+use crate::scan_block::*;
+use crate::scores::*;
+
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+type Key = (Vec<u8>, Vec<u8>);
+
+// index for the end of the linked list
+const NIL: usize = usize::MAX;
+
+/// Cached result in a doubly linked list, ordered from the most to the least recently used.
+struct Entry {
+    key: Key,
+    res: AlignResult,
+    prev: usize,
+    next: usize
+}
+
+/// A score-only block aligner that caches alignment results with a least recently used
+/// eviction policy.
+///
+/// The scoring matrix, gap costs, block sizes, and X-drop threshold are fixed when the
+/// aligner is created, so each cache entry is keyed by just the query and reference bytes.
+/// The full bytes are stored as the key, so hash collisions never return a wrong result.
+/// Lookups, insertions, and evictions take constant time (other than hashing the key).
+///
+/// Only `AlignResult`s are cached, so traceback is not supported.
+pub struct CachedAligner<'a, M: Matrix, const X_DROP: bool = false> {
+    block: Block<false, X_DROP>,
+    matrix: &'a M,
+    gaps: Gaps,
+    size: RangeInclusive<usize>,
+    x_drop: i32,
+    query_len: usize,
+    reference_len: usize,
+    capacity: usize,
+    cache: HashMap<Key, usize>,
+    entries: Vec<Entry>,
+    head: usize,
+    tail: usize,
+    hits: usize,
+    misses: usize
+}
+
+impl<'a, M: Matrix, const X_DROP: bool> CachedAligner<'a, M, { X_DROP }> {
+    /// Create a new cached aligner with upper bound query and reference lengths,
+    /// and a max number of cached results.
+    ///
+    /// See `Block::align` for the other parameters.
+    pub fn new(query_len: usize, reference_len: usize, matrix: &'a M, gaps: Gaps, size: RangeInclusive<usize>, x_drop: i32, capacity: usize) -> Self {
+        assert!(capacity > 0, "Cache capacity must be positive!");
+
+        Self {
+            block: Block::<false, X_DROP>::new(query_len, reference_len, *size.end()),
+            matrix,
+            gaps,
+            size,
+            x_drop,
+            query_len,
+            reference_len,
+            capacity,
+            cache: HashMap::with_capacity(capacity),
+            entries: Vec::with_capacity(capacity),
+            head: NIL,
+            tail: NIL,
+            hits: 0,
+            misses: 0
+        }
+    }
+
+    /// Align two sequences, or return the cached result if the same pair was aligned before.
+    ///
+    /// Unlike `Block::align`, this takes the original bytes.
+    pub fn align(&mut self, query: &[u8], reference: &[u8]) -> AlignResult {
+        assert!(query.len() <= self.query_len && reference.len() <= self.reference_len, "Sequences must not be longer than the upper bound lengths!");
+
+        // the key must be owned to insert it, so it is created even for lookups
+        let key = (query.to_owned(), reference.to_owned());
+
+        if let Some(&idx) = self.cache.get(&key) {
+            self.hits += 1;
+            self.unlink(idx);
+            self.push_front(idx);
+            return self.entries[idx].res;
+        }
+
+        self.misses += 1;
+
+        let max_size = *self.size.end();
+        let q = PaddedBytes::from_bytes::<M>(query, max_size);
+        let r = PaddedBytes::from_bytes::<M>(reference, max_size);
+        self.block.align(&q, &r, self.matrix, self.gaps, self.size.clone(), self.x_drop);
+        let res = self.block.res();
+
+        let idx = if self.entries.len() < self.capacity {
+            self.entries.push(Entry { key: key.clone(), res, prev: NIL, next: NIL });
+            self.entries.len() - 1
+        } else {
+            // evict the least recently used entry and reuse its slot
+            let idx = self.tail;
+            self.unlink(idx);
+            self.cache.remove(&self.entries[idx].key);
+            self.entries[idx].key = key.clone();
+            self.entries[idx].res = res;
+            idx
+        };
+
+        self.push_front(idx);
+        self.cache.insert(key, idx);
+        res
+    }
+
+    fn unlink(&mut self, idx: usize) {
+        let (prev, next) = (self.entries[idx].prev, self.entries[idx].next);
+        if prev == NIL {
+            self.head = next;
+        } else {
+            self.entries[prev].next = next;
+        }
+        if next == NIL {
+            self.tail = prev;
+        } else {
+            self.entries[next].prev = prev;
+        }
+    }
+
+    fn push_front(&mut self, idx: usize) {
+        self.entries[idx].prev = NIL;
+        self.entries[idx].next = self.head;
+        if self.head == NIL {
+            self.tail = idx;
+        } else {
+            self.entries[self.head].prev = idx;
+        }
+        self.head = idx;
+    }
+
+    /// Number of cached results.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Whether there are no cached results.
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Max number of cached results.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of alignments that were returned from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of alignments that had to be computed.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Fraction of alignments that were returned from the cache.
+    pub fn hit_rate(&self) -> f64 {
+        if self.hits + self.misses == 0 {
+            0.0
+        } else {
+            (self.hits as f64) / ((self.hits + self.misses) as f64)
+        }
+    }
+
+    /// Remove all cached results and reset the statistics.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.entries.clear();
+        self.head = NIL;
+        self.tail = NIL;
+        self.hits = 0;
+        self.misses = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_aligner() {
+        let mut a = CachedAligner::<_, false>::new(100, 100, &BLOSUM62, Gaps { open: -11, extend: -1 }, 16..=16, 0, 2);
+
+        assert_eq!(a.align(b"AARA", b"AAAA").score, 11);
+        assert_eq!(a.align(b"RRRR", b"AAAA").score, -4);
+        assert_eq!(a.align(b"AARA", b"AAAA").score, 11);
+        assert_eq!((a.hits(), a.misses(), a.len()), (1, 2, 2));
+
+        // evicts RRRR, which was used least recently
+        assert_eq!(a.align(b"AAAA", b"AAAA").score, 16);
+        assert_eq!(a.align(b"AARA", b"AAAA").score, 11);
+        assert_eq!(a.align(b"RRRR", b"AAAA").score, -4);
+        assert_eq!((a.hits(), a.misses(), a.len()), (2, 4, 2));
+        assert_eq!(a.hit_rate(), 2.0 / 6.0);
+
+        a.clear();
+        assert_eq!((a.hits(), a.misses(), a.len()), (0, 0, 0));
+        assert_eq!(a.align(b"RRRR", b"AAAA").score, -4);
+        assert_eq!(a.align(b"AAAA", b"AAAA").score, 16);
+        assert_eq!(a.align(b"RRRR", b"AAAA").score, -4);
+        // evicts AAAA
+        assert_eq!(a.align(b"AARA", b"AAAA").score, 11);
+        assert_eq!(a.align(b"RRRR", b"AAAA").score, -4);
+        assert_eq!((a.hits(), a.misses(), a.len()), (2, 3, 2));
+        assert_eq!(a.align(b"AAAA", b"AAAA").score, 16);
+        assert_eq!((a.hits(), a.misses(), a.len()), (2, 4, 2));
+    }
+}
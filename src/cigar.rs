@@ -1,6 +1,13 @@
 //! Data structures and functions for working with CIGAR strings.
 
-use std::fmt;
+use crate::scores::*;
+
+use core::{fmt, cmp};
+use alloc::vec::Vec;
+use alloc::vec;
+use alloc::string::String;
+use alloc::format;
+use alloc::borrow::ToOwned;
 
 /// A match/mismatch, insertion, or deletion operation.
 ///
@@ -53,10 +60,17 @@ impl Cigar {
         Cigar { s, idx }
     }
 
-    /// Clear this CIGAR string.
-    #[allow(dead_code)]
-    pub(crate) fn clear(&mut self, query_len: usize, reference_len: usize) {
-        self.s[..query_len + reference_len + 5].fill(OpLen { op: Operation::Sentinel, len: 0 });
+    /// Clear this CIGAR string so it can be reused for aligned sequences with certain
+    /// maximum lengths.
+    ///
+    /// The allocated space grows if the lengths are larger than the ones the CIGAR string
+    /// was created with, but it never shrinks.
+    pub fn clear(&mut self, query_len: usize, reference_len: usize) {
+        let len = query_len + reference_len + 5;
+        if self.s.len() < len {
+            self.s.resize(len, OpLen { op: Operation::Sentinel, len: 0 });
+        }
+        self.s[..len].fill(OpLen { op: Operation::Sentinel, len: 0 });
         self.idx = 1;
     }
 
@@ -131,6 +145,161 @@ impl Cigar {
         (a, b)
     }
 
+    /// Generate a three line visualization of the alignment, wrapped at `width` columns.
+    ///
+    /// Each block has the aligned query, a line that marks each column, and the aligned
+    /// reference. Columns are marked with `|` for identical bytes, `:` for different bytes with a
+    /// positive score in `matrix`, `.` for a score of zero, and a space for negative scores and gaps.
+    /// Blocks are separated by an empty line.
+    pub fn pretty<M: Matrix>(&self, q: &[u8], r: &[u8], matrix: &M, width: usize) -> String {
+        assert!(width > 0, "Width must be positive!");
+
+        let (a, b) = self.format(q, r);
+        let (a, b) = (a.as_bytes(), b.as_bytes());
+        let marks = a
+            .iter()
+            .zip(b)
+            .map(|(&x, &y)| {
+                if x == b'-' || y == b'-' {
+                    b' '
+                } else if M::convert_char(x) == M::convert_char(y) {
+                    b'|'
+                } else {
+                    match matrix.get(x, y) {
+                        s if s > 0 => b':',
+                        0 => b'.',
+                        _ => b' '
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut res = String::with_capacity(a.len() * 4);
+        for (k, ((a, m), b)) in a.chunks(width).zip(marks.chunks(width)).zip(b.chunks(width)).enumerate() {
+            if k > 0 {
+                res.push('\n');
+            }
+            for line in &[a, m, b] {
+                res.push_str(core::str::from_utf8(line).unwrap());
+                res.push('\n');
+            }
+        }
+        res
+    }
+
+    /// Generate the aligned query in A2M format, with the reference as the match states.
+    ///
+    /// Query characters that are aligned to the reference (match states) are uppercase,
+    /// insertions are lowercase, and deletions are `-`. Removing the lowercase characters
+    /// gives a string with the same length as the aligned region of the reference.
+    pub fn format_a2m(&self, q: &[u8]) -> String {
+        let mut a = String::with_capacity(self.idx);
+        let mut i = 0;
+
+        for &op_len in self.s[1..self.idx].iter().rev() {
+            match op_len.op {
+                Operation::M | Operation::Eq | Operation::X => {
+                    for _k in 0..op_len.len {
+                        a.push(q[i].to_ascii_uppercase() as char);
+                        i += 1;
+                    }
+                },
+                Operation::I => {
+                    for _k in 0..op_len.len {
+                        a.push(q[i].to_ascii_lowercase() as char);
+                        i += 1;
+                    }
+                },
+                Operation::D => {
+                    for _k in 0..op_len.len {
+                        a.push('-');
+                    }
+                },
+                _ => continue
+            }
+        }
+
+        a
+    }
+
+    /// Generate the tab-separated fields of a GFA `L` (link) line for an overlap alignment
+    /// between two reads.
+    ///
+    /// The query is the `to` segment and the reference is the `from` segment, and the
+    /// orientations are `-` if the corresponding read was reverse complemented before aligning.
+    /// Insertions and deletions at the start and end of the CIGAR string are overhangs of the
+    /// reads that are outside the overlap, so they are removed from the overlap CIGAR string.
+    /// If there is no overlap, then the CIGAR string is `*`.
+    ///
+    /// The line does not end with a newline.
+    pub fn format_gfa_link(&self, from: &str, from_reverse: bool, to: &str, to_reverse: bool) -> String {
+        let ops = &self.s[1..self.idx];
+        let is_gap = |op_len: &OpLen| op_len.op == Operation::I || op_len.op == Operation::D;
+        // operations are stored in reverse order
+        let end = ops.iter().position(|op_len| !is_gap(op_len)).unwrap_or(ops.len());
+        let start = ops.iter().rposition(|op_len| !is_gap(op_len)).map(|i| i + 1).unwrap_or(end);
+
+        let mut overlap = String::new();
+        for &op_len in ops[end..start].iter().rev() {
+            let c = match op_len.op {
+                Operation::M => 'M',
+                Operation::Eq => '=',
+                Operation::X => 'X',
+                Operation::I => 'I',
+                Operation::D => 'D',
+                _ => continue
+            };
+            overlap.push_str(&format!("{}{}", op_len.len, c));
+        }
+        if overlap.is_empty() {
+            overlap.push('*');
+        }
+
+        let orient = |reverse: bool| if reverse { '-' } else { '+' };
+        format!("L\t{}\t{}\t{}\t{}\t{}", from, orient(from_reverse), to, orient(to_reverse), overlap)
+    }
+
+    /// Generate a CIGAR string for SAM records.
+    ///
+    /// If `extended` is false, then matches and mismatches (`=` and `X`) are merged into `M`.
+    /// `query_clip` is the number of query bytes before and after the aligned region, which are
+    /// added as soft clips (`S`). For a local alignment ending at `query_idx`, this is
+    /// `(query_idx - query_len, |q| - query_idx)`, where `query_len` is from `aligned_lens`.
+    /// If there are no operations and no clips, then the CIGAR string is `*`.
+    pub fn to_sam_string(&self, extended: bool, query_clip: (usize, usize)) -> String {
+        let mut runs: Vec<(char, usize)> = Vec::with_capacity(self.idx + 1);
+        let mut push = |c: char, len: usize| {
+            if len == 0 {
+                return;
+            }
+            match runs.last_mut() {
+                Some(last) if last.0 == c => last.1 += len,
+                _ => runs.push((c, len))
+            }
+        };
+
+        push('S', query_clip.0);
+        for &op_len in self.s[1..self.idx].iter().rev() {
+            let c = match op_len.op {
+                Operation::M => 'M',
+                Operation::Eq if !extended => 'M',
+                Operation::X if !extended => 'M',
+                Operation::Eq => '=',
+                Operation::X => 'X',
+                Operation::I => 'I',
+                Operation::D => 'D',
+                _ => continue
+            };
+            push(c, op_len.len);
+        }
+        push('S', query_clip.1);
+
+        if runs.is_empty() {
+            return "*".to_owned();
+        }
+        runs.iter().map(|(c, len)| format!("{}{}", len, c)).collect()
+    }
+
     /// Create a copy of the operations in the CIGAR string and
     /// ensure that the vector is provided in the correct order.
     ///
@@ -142,8 +311,479 @@ impl Cigar {
             .map(|&op_len| op_len)
             .collect::<Vec<OpLen>>()
     }
+
+    /// Number of query and reference bytes covered by the operations, as
+    /// `(query_len, reference_len)`.
+    ///
+    /// For a traceback that ends at `(query_idx, reference_idx)`, the alignment starts at
+    /// `(query_idx - query_len, reference_idx - reference_len)`. This is useful for
+    /// finding where a local alignment starts.
+    pub fn aligned_lens(&self) -> (usize, usize) {
+        self.s[1..self.idx].iter().fold((0, 0), |(q, r), op_len| match op_len.op {
+            Operation::M | Operation::Eq | Operation::X => (q + op_len.len, r + op_len.len),
+            Operation::I => (q + op_len.len, r),
+            Operation::D => (q, r + op_len.len),
+            _ => (q, r)
+        })
+    }
+
+    /// Create a new CIGAR string with only the operations that overlap the reference
+    /// positions `start..end`.
+    ///
+    /// Reference positions are relative to the start of the alignment. Operations that
+    /// straddle the window boundaries are shortened. Insertions are kept only if they are
+    /// strictly inside the window, so the trimmed CIGAR string always starts and ends with
+    /// an operation that consumes the reference.
+    pub fn trim_to_ref_range(&self, start: usize, end: usize) -> Cigar {
+        assert!(start <= end, "Reference window start must not be after the end!");
+
+        let mut ops = Vec::with_capacity(self.len());
+        let mut query_len = 0;
+        let mut j = 0;
+
+        for &op_len in self.s[1..self.idx].iter().rev() {
+            match op_len.op {
+                Operation::I => {
+                    if j > start && j < end {
+                        ops.push(op_len);
+                        query_len += op_len.len;
+                    }
+                },
+                Operation::M | Operation::Eq | Operation::X | Operation::D => {
+                    let op_start = cmp::max(j, start);
+                    let op_end = cmp::min(j + op_len.len, end);
+                    if op_start < op_end {
+                        ops.push(OpLen { op: op_len.op, len: op_end - op_start });
+                        if op_len.op != Operation::D {
+                            query_len += op_end - op_start;
+                        }
+                    }
+                    j += op_len.len;
+                },
+                _ => continue
+            }
+        }
+
+        Self::from_op_lens(&ops, query_len, end - start)
+    }
+
+    /// Create a CIGAR string from a pair of aligned sequences that contain `-` gap characters.
+    ///
+    /// This allows externally-produced alignments to be scored with `score`.
+    /// Matches and mismatches are resolved (case insensitive) and columns where both
+    /// sequences have gaps are skipped.
+    pub fn from_gapped(q: &[u8], r: &[u8]) -> Cigar {
+        assert_eq!(q.len(), r.len(), "Aligned sequences must have the same length!");
+
+        let mut ops: Vec<OpLen> = Vec::new();
+        let mut query_len = 0;
+        let mut reference_len = 0;
+
+        for (&a, &b) in q.iter().zip(r) {
+            let op = match (a, b) {
+                (b'-', b'-') => continue,
+                (_, b'-') => Operation::I,
+                (b'-', _) => Operation::D,
+                _ => if a.eq_ignore_ascii_case(&b) { Operation::Eq } else { Operation::X }
+            };
+
+            if op != Operation::D {
+                query_len += 1;
+            }
+            if op != Operation::I {
+                reference_len += 1;
+            }
+
+            match ops.last_mut() {
+                Some(op_len) if op_len.op == op => op_len.len += 1,
+                _ => ops.push(OpLen { op, len: 1 })
+            }
+        }
+
+        Self::from_op_lens(&ops, query_len, reference_len)
+    }
+
+    /// Compute the score of the alignment represented by this CIGAR string.
+    ///
+    /// The bytes of `q` and `r` should start at the beginning of the alignment and should not
+    /// contain gap characters. A gap of length n costs `open + extend * (n - 1)`, like in `Block::align`.
+    pub fn score<M: Matrix>(&self, q: &[u8], r: &[u8], matrix: &M, gaps: Gaps) -> i32 {
+        let mut res = 0i32;
+        let mut i = 0;
+        let mut j = 0;
+
+        for &op_len in self.s[1..self.idx].iter().rev() {
+            match op_len.op {
+                Operation::M | Operation::Eq | Operation::X => {
+                    for _k in 0..op_len.len {
+                        res += matrix.get(q[i], r[j]) as i32;
+                        i += 1;
+                        j += 1;
+                    }
+                },
+                Operation::I => {
+                    res += (gaps.open as i32) + (gaps.extend as i32) * ((op_len.len as i32) - 1);
+                    i += op_len.len;
+                },
+                Operation::D => {
+                    res += (gaps.open as i32) + (gaps.extend as i32) * ((op_len.len as i32) - 1);
+                    j += op_len.len;
+                },
+                _ => continue
+            }
+        }
+
+        res
+    }
+
+    /// Count the matches, mismatches, and gaps in the alignment represented by this CIGAR string.
+    ///
+    /// The bytes of `q` and `r` should start at the beginning of the alignment. Matches are
+    /// determined after converting bytes with the alphabet of the `Matrix` type, so `M`
+    /// operations are split into matches and mismatches. Aligned pairs with a positive score
+    /// in `matrix` are also counted as positives.
+    pub fn stats<M: Matrix>(&self, q: &[u8], r: &[u8], matrix: &M) -> AlignStats {
+        let mut res = AlignStats { matches: 0, mismatches: 0, positives: 0, insertions: 0, deletions: 0, gap_opens: 0 };
+        let mut i = 0;
+        let mut j = 0;
+
+        for &op_len in self.s[1..self.idx].iter().rev() {
+            match op_len.op {
+                Operation::M | Operation::Eq | Operation::X => {
+                    for _k in 0..op_len.len {
+                        if M::convert_char(q[i]) == M::convert_char(r[j]) {
+                            res.matches += 1;
+                        } else {
+                            res.mismatches += 1;
+                        }
+                        if matrix.get(q[i], r[j]) > 0 {
+                            res.positives += 1;
+                        }
+                        i += 1;
+                        j += 1;
+                    }
+                },
+                Operation::I => {
+                    res.insertions += op_len.len;
+                    res.gap_opens += 1;
+                    i += op_len.len;
+                },
+                Operation::D => {
+                    res.deletions += op_len.len;
+                    res.gap_opens += 1;
+                    j += op_len.len;
+                },
+                _ => continue
+            }
+        }
+
+        res
+    }
+
+    /// Compute the percent identity of the alignment in successive windows of `window` reference bytes.
+    ///
+    /// The identity of a window is the number of matches divided by the number of alignment columns
+    /// in that window, including insertions and deletions. Insertions belong to the window of the next
+    /// reference byte. The last window may be shorter than `window`.
+    ///
+    /// The bytes of `q` and `r` should start at the beginning of the alignment, and matches are
+    /// determined by comparing bytes (case insensitive).
+    pub fn window_identity(&self, q: &[u8], r: &[u8], window: usize) -> Vec<f32> {
+        assert!(window > 0, "Window size must be positive!");
+
+        let mut matches = Vec::new();
+        let mut columns = Vec::new();
+        let mut i = 0;
+        let mut j = 0;
+
+        let mut add = |j: usize, is_match: bool| {
+            let w = j / window;
+            if w >= columns.len() {
+                matches.resize(w + 1, 0usize);
+                columns.resize(w + 1, 0usize);
+            }
+            matches[w] += is_match as usize;
+            columns[w] += 1;
+        };
+
+        for &op_len in self.s[1..self.idx].iter().rev() {
+            match op_len.op {
+                Operation::M | Operation::Eq | Operation::X => {
+                    for _k in 0..op_len.len {
+                        add(j, q[i].eq_ignore_ascii_case(&r[j]));
+                        i += 1;
+                        j += 1;
+                    }
+                },
+                Operation::I => {
+                    for _k in 0..op_len.len {
+                        add(j, false);
+                        i += 1;
+                    }
+                },
+                Operation::D => {
+                    for _k in 0..op_len.len {
+                        add(j, false);
+                        j += 1;
+                    }
+                },
+                _ => continue
+            }
+        }
+
+        // insertions after the end of the reference belong to the last window
+        if j > 0 && columns.len() > (j - 1) / window + 1 {
+            let extra = columns.pop().unwrap();
+            matches.pop();
+            *columns.last_mut().unwrap() += extra;
+        }
+
+        matches.iter().zip(&columns).map(|(&m, &c)| (m as f32) / (c as f32)).collect()
+    }
+
+    /// Compare the score of this alignment with the score of another alignment of the
+    /// same sequences, using the same scoring scheme.
+    ///
+    /// Returns this score minus the other score, so a positive value means that this
+    /// alignment scores higher. See `score` for the expected inputs.
+    pub fn score_diff<M: Matrix>(&self, other: &Cigar, q: &[u8], r: &[u8], matrix: &M, gaps: Gaps) -> i32 {
+        self.score(q, r, matrix, gaps) - other.score(q, r, matrix, gaps)
+    }
+
+    /// Get the length of the longest run of consecutive matches in the alignment.
+    ///
+    /// This can be used to require a minimum exact match seed length of `k`, by rejecting
+    /// alignments where this is less than `k`. This filters out spurious alignments of
+    /// low-complexity regions that never match exactly for long.
+    ///
+    /// The bytes of `q` and `r` should start at the beginning of the alignment, and matches are
+    /// determined by comparing bytes (case insensitive), so this works for both `M` and
+    /// `=`/`X` operations.
+    pub fn longest_match_run(&self, q: &[u8], r: &[u8]) -> usize {
+        let mut res = 0;
+        let mut curr = 0;
+        let mut i = 0;
+        let mut j = 0;
+
+        for &op_len in self.s[1..self.idx].iter().rev() {
+            match op_len.op {
+                Operation::M | Operation::Eq | Operation::X => {
+                    for _k in 0..op_len.len {
+                        if q[i].eq_ignore_ascii_case(&r[j]) {
+                            curr += 1;
+                            res = cmp::max(res, curr);
+                        } else {
+                            curr = 0;
+                        }
+                        i += 1;
+                        j += 1;
+                    }
+                },
+                Operation::I => {
+                    curr = 0;
+                    i += op_len.len;
+                },
+                Operation::D => {
+                    curr = 0;
+                    j += op_len.len;
+                },
+                _ => continue
+            }
+        }
+
+        res
+    }
+
+    /// Join CIGAR strings of consecutive alignment segments into a single CIGAR string.
+    ///
+    /// Each segment is paired with its start coordinates `(query_start, reference_start)`.
+    /// The coordinate spans must be contiguous: in both the query and the reference, every segment
+    /// must start at or after the end of the previous segment (or the start of the sequences, at
+    /// `(0, 0)`). The unaligned bytes between segments are added as insertions followed by
+    /// deletions. Runs of the same operation at the boundaries of segments are merged.
+    ///
+    /// Returns an error with the index of the first segment that starts before the end of the
+    /// previous segment.
+    pub fn concat(segments: &[(Cigar, (usize, usize))]) -> Result<Cigar, CigarConcatError> {
+        let mut ops: Vec<OpLen> = Vec::new();
+        // end of the previous segment
+        let mut query_len = 0;
+        let mut reference_len = 0;
+
+        for (idx, (cigar, (query_start, reference_start))) in segments.iter().enumerate() {
+            if *query_start < query_len || *reference_start < reference_len {
+                return Err(CigarConcatError::Overlap(idx));
+            }
+
+            let gaps = [
+                OpLen { op: Operation::I, len: query_start - query_len },
+                OpLen { op: Operation::D, len: reference_start - reference_len }
+            ];
+
+            for &op_len in gaps.iter().chain(cigar.s[1..cigar.idx].iter().rev()) {
+                if op_len.len == 0 {
+                    continue;
+                }
+                match op_len.op {
+                    Operation::M | Operation::Eq | Operation::X => {
+                        query_len += op_len.len;
+                        reference_len += op_len.len;
+                    },
+                    Operation::I => query_len += op_len.len,
+                    Operation::D => reference_len += op_len.len,
+                    _ => continue
+                }
+                match ops.last_mut() {
+                    Some(last) if last.op == op_len.op => last.len += op_len.len,
+                    _ => ops.push(op_len)
+                }
+            }
+        }
+
+        Ok(Self::from_op_lens(&ops, query_len, reference_len))
+    }
+
+    /// Parse a CIGAR string from a SAM record.
+    ///
+    /// Supports the `M`, `=`, `X`, `I`, and `D` operations. Soft and hard clips (`S` and `H`)
+    /// are skipped since they are not part of the alignment. The CIGAR string `*` is parsed
+    /// as an empty CIGAR string. Runs of the same operation are merged.
+    pub fn from_sam_string(s: &str) -> Result<Cigar, CigarParseError> {
+        let mut ops: Vec<OpLen> = Vec::new();
+        let mut query_len = 0;
+        let mut reference_len = 0;
+
+        if s == "*" {
+            return Ok(Self::from_op_lens(&ops, 0, 0));
+        }
+
+        let mut len: Option<usize> = None;
+        for (i, c) in s.char_indices() {
+            if let Some(d) = c.to_digit(10) {
+                len = Some(len.unwrap_or(0).checked_mul(10).and_then(|l| l.checked_add(d as usize)).ok_or(CigarParseError::InvalidLen(i))?);
+                continue;
+            }
+
+            let l = len.take().ok_or(CigarParseError::InvalidLen(i))?;
+            if l == 0 {
+                return Err(CigarParseError::InvalidLen(i));
+            }
+            let op = match c {
+                'M' => Operation::M,
+                '=' => Operation::Eq,
+                'X' => Operation::X,
+                'I' => Operation::I,
+                'D' => Operation::D,
+                'S' | 'H' => continue,
+                _ => return Err(CigarParseError::InvalidOp(c))
+            };
+
+            match op {
+                Operation::I => query_len += l,
+                Operation::D => reference_len += l,
+                _ => {
+                    query_len += l;
+                    reference_len += l;
+                }
+            }
+            match ops.last_mut() {
+                Some(last) if last.op == op => last.len += l,
+                _ => ops.push(OpLen { op, len: l })
+            }
+        }
+
+        if len.is_some() {
+            return Err(CigarParseError::MissingOp);
+        }
+
+        Ok(Self::from_op_lens(&ops, query_len, reference_len))
+    }
+
+    pub(crate) fn from_op_lens(ops: &[OpLen], query_len: usize, reference_len: usize) -> Cigar {
+        let mut res = Cigar::new(query_len, reference_len);
+        for (i, &op_len) in ops.iter().rev().enumerate() {
+            res.s[1 + i] = op_len;
+        }
+        res.idx = 1 + ops.len();
+        res
+    }
+}
+
+/// Counts of different types of alignment columns.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct AlignStats {
+    /// Number of aligned pairs of identical bytes.
+    pub matches: usize,
+    /// Number of aligned pairs of different bytes.
+    pub mismatches: usize,
+    /// Number of aligned pairs with a positive score.
+    pub positives: usize,
+    /// Number of query bytes aligned to gaps.
+    pub insertions: usize,
+    /// Number of reference bytes aligned to gaps.
+    pub deletions: usize,
+    /// Number of gaps (runs of insertions or deletions).
+    pub gap_opens: usize
 }
 
+impl AlignStats {
+    /// Number of columns in the alignment, including gaps.
+    pub fn aligned_len(&self) -> usize {
+        self.matches + self.mismatches + self.insertions + self.deletions
+    }
+
+    /// Fraction of alignment columns that are matches, or 0 if the alignment is empty.
+    pub fn identity(&self) -> f32 {
+        let len = self.aligned_len();
+        if len == 0 {
+            return 0.0;
+        }
+        (self.matches as f32) / (len as f32)
+    }
+}
+
+/// Error when parsing a CIGAR string.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum CigarParseError {
+    /// The operation at a byte index does not have a length, or the length is zero or too large.
+    InvalidLen(usize),
+    /// An operation character that is not supported.
+    InvalidOp(char),
+    /// The CIGAR string ends with a length and no operation.
+    MissingOp
+}
+
+impl fmt::Display for CigarParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CigarParseError::InvalidLen(i) => write!(f, "invalid operation length at index {}", i),
+            CigarParseError::InvalidOp(c) => write!(f, "invalid operation: {}", c),
+            CigarParseError::MissingOp => write!(f, "missing operation after the last length")
+        }
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+impl std::error::Error for CigarParseError {}
+
+/// Error when joining CIGAR strings of alignment segments.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum CigarConcatError {
+    /// The segment at an index starts before the end of the previous segment.
+    Overlap(usize)
+}
+
+impl fmt::Display for CigarConcatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CigarConcatError::Overlap(i) => write!(f, "segment {} starts before the end of the previous segment", i)
+        }
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+impl std::error::Error for CigarConcatError {}
+
 impl fmt::Display for Cigar {
     /// Print a CIGAR string in standard CIGAR format.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -161,3 +801,175 @@ impl fmt::Display for Cigar {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_ops(ops: &[(Operation, usize)]) -> Cigar {
+        let mut cigar = Cigar::new(ops.iter().map(|&(_, l)| l).sum(), 0);
+        for &(op, len) in ops.iter().rev() {
+            for _ in 0..len {
+                unsafe { cigar.add(op); }
+            }
+        }
+        cigar
+    }
+
+    #[test]
+    fn test_to_sam_string() {
+        let cigar = Cigar::from_gapped(b"ACGT-ACCTA", b"ACGTTACG--");
+        assert_eq!(cigar.to_sam_string(true, (0, 0)), "4=1D2=1X2I");
+        assert_eq!(cigar.to_sam_string(false, (0, 0)), "4M1D3M2I");
+        assert_eq!(cigar.to_sam_string(false, (3, 1)), "3S4M1D3M2I1S");
+        assert_eq!(Cigar::new(0, 0).to_sam_string(false, (0, 0)), "*");
+    }
+
+    #[test]
+    fn test_from_sam_string() {
+        let cigar = Cigar::from_sam_string("3S4=1D2=1X2I1S").unwrap();
+        assert_eq!(cigar.to_string(), "4=1D2=1X2I");
+        assert_eq!(cigar.score(b"ACGTACCTA", b"ACGTTACG", &NW1, Gaps { open: -2, extend: -1 }), 6 - 1 - 2 - 3);
+        assert_eq!(Cigar::from_sam_string("2M3M10D").unwrap().to_string(), "5M10D");
+        assert_eq!(Cigar::from_sam_string("*").unwrap().len(), 0);
+
+        assert_eq!(Cigar::from_sam_string("M").err(), Some(CigarParseError::InvalidLen(0)));
+        assert_eq!(Cigar::from_sam_string("2M0I").err(), Some(CigarParseError::InvalidLen(3)));
+        assert_eq!(Cigar::from_sam_string("2M3N").err(), Some(CigarParseError::InvalidOp('N')));
+        assert_eq!(Cigar::from_sam_string("2M3").err(), Some(CigarParseError::MissingOp));
+    }
+
+    #[test]
+    fn test_stats() {
+        let cigar = Cigar::from_gapped(b"ACGT-ACCTA", b"ACGTTACG--");
+        let stats = cigar.stats(b"ACGTACCTA", b"ACGTTACG", &NW1);
+        assert_eq!(stats, AlignStats { matches: 6, mismatches: 1, positives: 6, insertions: 2, deletions: 1, gap_opens: 2 });
+        assert_eq!(stats.aligned_len(), 10);
+        assert_eq!(stats.identity(), 0.6);
+
+        let cigar = Cigar::from_gapped(b"KAW", b"RAY");
+        let stats = cigar.stats(b"KAW", b"RAY", &BLOSUM62);
+        assert_eq!(stats, AlignStats { matches: 1, mismatches: 2, positives: 3, insertions: 0, deletions: 0, gap_opens: 0 });
+        assert_eq!(Cigar::new(0, 0).stats(b"", b"", &NW1).identity(), 0.0);
+    }
+
+    #[test]
+    fn test_pretty() {
+        let cigar = Cigar::from_gapped(b"ACGT-ACCTA", b"ACGTTACG--");
+        assert_eq!(cigar.pretty(b"ACGTACCTA", b"ACGTTACG", &NW1, 6), "ACGT-A\n|||| |\nACGTTA\n\nCCTA\n|   \nCG--\n");
+
+        let cigar = Cigar::from_gapped(b"KAWC", b"RAYG");
+        assert_eq!(cigar.pretty(b"KAWC", b"RAYG", &BLOSUM62, 80), "KAWC\n:|: \nRAYG\n");
+        assert_eq!(Cigar::new(0, 0).pretty(b"", b"", &NW1, 80), "");
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut cigar = Cigar::new(1, 1);
+        cigar.clear(4, 4);
+        for &op in &[Operation::Eq, Operation::Eq, Operation::I, Operation::Eq, Operation::Eq] {
+            unsafe { cigar.add(op); }
+        }
+        assert_eq!(cigar.to_string(), "2=1I2=");
+
+        cigar.clear(1, 1);
+        assert_eq!(cigar.len(), 0);
+        assert_eq!(cigar.s.len(), 13);
+    }
+
+    #[test]
+    fn test_format_gfa_link() {
+        use Operation::*;
+        // the end of the reference overlaps the start of the query
+        let cigar = from_ops(&[(D, 5), (Eq, 3), (I, 1), (X, 1), (Eq, 4), (I, 6)]);
+        assert_eq!(cigar.format_gfa_link("r1", false, "r2", true), "L\tr1\t+\tr2\t-\t3=1I1X4=");
+
+        let cigar = from_ops(&[(D, 5), (I, 6)]);
+        assert_eq!(cigar.format_gfa_link("r1", true, "r2", false), "L\tr1\t-\tr2\t+\t*");
+    }
+
+    #[test]
+    fn test_trim_to_ref_range() {
+        use Operation::*;
+        let cigar = from_ops(&[(Eq, 3), (I, 2), (X, 1), (D, 4), (Eq, 5)]);
+        assert_eq!(cigar.to_string(), "3=2I1X4D5=");
+
+        assert_eq!(cigar.trim_to_ref_range(0, 13).to_string(), "3=2I1X4D5=");
+        assert_eq!(cigar.trim_to_ref_range(1, 3).to_string(), "2=");
+        assert_eq!(cigar.trim_to_ref_range(0, 3).to_string(), "3=");
+        assert_eq!(cigar.trim_to_ref_range(3, 6).to_string(), "1X2D");
+        assert_eq!(cigar.trim_to_ref_range(2, 6).to_string(), "1=2I1X2D");
+        assert_eq!(cigar.trim_to_ref_range(6, 20).to_string(), "2D5=");
+        assert_eq!(cigar.trim_to_ref_range(4, 4).to_string(), "");
+    }
+
+    #[test]
+    fn test_format_a2m() {
+        use Operation::*;
+        let cigar = from_ops(&[(Eq, 2), (I, 2), (X, 1), (D, 2), (Eq, 1)]);
+        assert_eq!(cigar.format_a2m(b"acGTTa"), "ACgtT--A");
+    }
+
+    #[test]
+    fn test_from_gapped() {
+        let cigar = Cigar::from_gapped(b"AC--GTT-A", b"AcT--TAGA");
+        assert_eq!(cigar.to_string(), "2=1D1I1=1X1D1=");
+        let gaps = Gaps { open: -2, extend: -1 };
+        assert_eq!(cigar.score(b"ACGTTA", b"ACTTAGA", &NW1, gaps), 4 - 1 - 6);
+
+        let cigar = Cigar::from_gapped(b"AA---A", b"AAAAAA");
+        assert_eq!(cigar.score(b"AAA", b"AAAAAA", &NW1, gaps), 3 - 4);
+    }
+
+    #[test]
+    fn test_window_identity() {
+        let cigar = Cigar::from_gapped(b"ACGT-ACCTA", b"ACGTTACG--");
+        assert_eq!(cigar.window_identity(b"ACGTACCTA", b"ACGTTACG", 4), vec![1.0, 2.0 / 6.0]);
+        assert_eq!(cigar.window_identity(b"ACGTACCTA", b"ACGTTACG", 3), vec![1.0, 2.0 / 3.0, 0.25]);
+        assert_eq!(cigar.window_identity(b"acgtaccta", b"ACGTTACG", 4), vec![1.0, 2.0 / 6.0]);
+        assert_eq!(Cigar::new(0, 0).window_identity(b"", b"", 4), vec![]);
+    }
+
+    #[test]
+    fn test_aligned_lens() {
+        assert_eq!(Cigar::from_gapped(b"AC-GT", b"ACAG-").aligned_lens(), (4, 4));
+        assert_eq!(Cigar::from_gapped(b"ACGTT", b"A--TT").aligned_lens(), (5, 3));
+        assert_eq!(Cigar::new(0, 0).aligned_lens(), (0, 0));
+    }
+
+    #[test]
+    fn test_concat() {
+        // a is 3 query bytes and 4 reference bytes long, b is 2 and 2, and c is 1 and 2
+        let segments = || (Cigar::from_gapped(b"AC-G", b"ACTG"), Cigar::from_gapped(b"GT", b"GA"), Cigar::from_gapped(b"A-", b"AA"));
+
+        let (a, b, c) = segments();
+        assert_eq!(Cigar::concat(&[(a, (0, 0)), (b, (3, 4)), (c, (7, 7))]).unwrap().to_string(), "2=1D2=1X2I1D1=1D");
+        assert_eq!(Cigar::concat(&[]).unwrap().to_string(), "");
+
+        let (a, b, _) = segments();
+        assert_eq!(Cigar::concat(&[(a, (0, 0)), (b, (2, 4))]).err(), Some(CigarConcatError::Overlap(1)));
+        let (a, b, _) = segments();
+        assert_eq!(Cigar::concat(&[(a, (0, 0)), (b, (3, 3))]).err(), Some(CigarConcatError::Overlap(1)));
+        let (a, b, c) = segments();
+        assert_eq!(Cigar::concat(&[(a, (1, 1)), (b, (5, 5)), (c, (6, 8))]).err(), Some(CigarConcatError::Overlap(2)));
+    }
+
+    #[test]
+    fn test_score_diff() {
+        let gaps = Gaps { open: -2, extend: -1 };
+        let a = Cigar::from_gapped(b"AC-GT", b"ACAGT");
+        let b = Cigar::from_gapped(b"ACG-T", b"ACAGT");
+        assert_eq!(a.score_diff(&b, b"ACGT", b"ACAGT", &NW1, gaps), 2);
+        assert_eq!(b.score_diff(&a, b"ACGT", b"ACAGT", &NW1, gaps), -2);
+    }
+
+    #[test]
+    fn test_longest_match_run() {
+        let cigar = Cigar::from_gapped(b"ACGT-ACCTA", b"ACGTTACG--");
+        assert_eq!(cigar.longest_match_run(b"ACGTACCTA", b"ACGTTACG"), 4);
+        let cigar = Cigar::from_gapped(b"ACATACGTAC", b"ACGTACGTAC");
+        assert_eq!(cigar.longest_match_run(b"ACATACGTAC", b"ACGTACGTAC"), 7);
+        assert_eq!(cigar.longest_match_run(b"acatacgtac", b"ACGTACGTAC"), 7);
+        assert_eq!(Cigar::new(0, 0).longest_match_run(b"", b""), 0);
+    }
+}
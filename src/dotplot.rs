@@ -0,0 +1,87 @@
+//! Sparse dotplots of exact k-mer matches between two sequences.
+//!
+//! This does not depend on the alignment algorithm, but it is useful for visualizing
+//! where the alignment path between two sequences should go.
+
+use std::collections::HashMap;
+
+/// A run of overlapping k-mer matches along a single diagonal of the dotplot.
+///
+/// The run covers `a[a_idx..a_idx + len]` and `b[b_idx..b_idx + len]`, which are identical.
+/// The length is always at least `k`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Diagonal {
+    pub a_idx: usize,
+    pub b_idx: usize,
+    pub len: usize
+}
+
+/// Compute a sparse dotplot of all exact k-mer matches between `a` and `b`.
+///
+/// Overlapping k-mer matches along the same diagonal are merged into a single `Diagonal`.
+/// The returned diagonals are sorted by their start position in `a`, then by their
+/// start position in `b`.
+///
+/// Bytes are compared exactly, so sequences should be uppercased beforehand if
+/// case should be ignored.
+pub fn dotplot(a: &[u8], b: &[u8], k: usize) -> Vec<Diagonal> {
+    assert!(k > 0, "K-mer size must be positive!");
+    let mut res = Vec::new();
+
+    if a.len() < k || b.len() < k {
+        return res;
+    }
+
+    let mut kmers: HashMap<&[u8], Vec<usize>> = HashMap::new();
+    for j in 0..=b.len() - k {
+        kmers.entry(&b[j..j + k]).or_default().push(j);
+    }
+
+    // index of the latest run in res for each diagonal
+    let mut runs: HashMap<isize, usize> = HashMap::new();
+
+    for i in 0..=a.len() - k {
+        if let Some(js) = kmers.get(&a[i..i + k]) {
+            for &j in js {
+                let diag = (i as isize) - (j as isize);
+
+                if let Some(&idx) = runs.get(&diag) {
+                    let run = &mut res[idx];
+                    // extend the run if the previous k-mer on the same diagonal also matched
+                    if run.a_idx + run.len - k + 1 == i {
+                        run.len += 1;
+                        continue;
+                    }
+                }
+
+                runs.insert(diag, res.len());
+                res.push(Diagonal { a_idx: i, b_idx: j, len: k });
+            }
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dotplot() {
+        assert_eq!(dotplot(b"", b"ACGT", 2), vec![]);
+        assert_eq!(dotplot(b"ACGT", b"TTTT", 2), vec![]);
+
+        assert_eq!(dotplot(b"ACGTACGT", b"ACGT", 3), vec![
+            Diagonal { a_idx: 0, b_idx: 0, len: 4 },
+            Diagonal { a_idx: 4, b_idx: 0, len: 4 }
+        ]);
+
+        assert_eq!(dotplot(b"AAAA", b"AAA", 2), vec![
+            Diagonal { a_idx: 0, b_idx: 0, len: 3 },
+            Diagonal { a_idx: 0, b_idx: 1, len: 2 },
+            Diagonal { a_idx: 1, b_idx: 0, len: 3 },
+            Diagonal { a_idx: 2, b_idx: 0, len: 2 }
+        ]);
+    }
+}
@@ -0,0 +1,94 @@
+//! Simple reader for sequences and quality scores in FASTQ files.
+
+use crate::scan_block::*;
+use crate::scores::*;
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+use std::path::Path;
+
+/// Iterator over the records in a FASTQ file.
+///
+/// Each record is a name, a padded sequence, and the Phred quality scores
+/// (with the ASCII offset of 33 removed).
+pub struct FastqReader<M: Matrix, R: BufRead> {
+    lines: Lines<R>,
+    block_size: usize,
+    _matrix: std::marker::PhantomData<M>
+}
+
+impl<M: Matrix, R: BufRead> FastqReader<M, R> {
+    /// Create a FASTQ reader from any buffered reader.
+    ///
+    /// Sequences are padded with `block_size`, like in `PaddedBytes::from_bytes`.
+    pub fn new(reader: R, block_size: usize) -> Self {
+        Self {
+            lines: reader.lines(),
+            block_size,
+            _matrix: std::marker::PhantomData
+        }
+    }
+
+    fn next_line(&mut self) -> Option<String> {
+        self.lines.next().map(|l| l.expect("Error reading FASTQ file!"))
+    }
+}
+
+impl<M: Matrix, R: BufRead> Iterator for FastqReader<M, R> {
+    type Item = (String, PaddedBytes, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let header = loop {
+            let line = self.next_line()?;
+            if !line.is_empty() {
+                break line;
+            }
+        };
+        assert!(header.starts_with('@'), "FASTQ record must start with '@'!");
+        let name = header[1..].to_owned();
+
+        let seq = self.next_line().expect("FASTQ record is missing the sequence!");
+        let plus = self.next_line().expect("FASTQ record is missing the '+' line!");
+        assert!(plus.starts_with('+'), "FASTQ record must have a '+' line after the sequence!");
+        let qual = self.next_line().expect("FASTQ record is missing the quality scores!");
+        assert_eq!(seq.len(), qual.len(), "FASTQ sequence and quality scores must have the same length!");
+
+        let qual = qual
+            .bytes()
+            .map(|q| {
+                assert!(q >= 33, "FASTQ quality scores must be at least 33 in ASCII!");
+                q - 33
+            })
+            .collect::<Vec<u8>>();
+
+        Some((name, PaddedBytes::from_bytes::<M>(seq.as_bytes(), self.block_size), qual))
+    }
+}
+
+/// Read a FASTQ file and iterate over its records.
+///
+/// Multi-line FASTQ records are not supported. This panics if the file cannot be read
+/// or if a record is malformed.
+pub fn read_fastq<M: Matrix, P: AsRef<Path>>(path: P, block_size: usize) -> FastqReader<M, BufReader<File>> {
+    let file = File::open(path).expect("Error opening FASTQ file!");
+    FastqReader::new(BufReader::new(file), block_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fastq() {
+        let s = b"@read1 desc\nACGT\n+\nII#5\n\n@read2\nTT\n+read2\n!!\n";
+        let records = FastqReader::<NucMatrix, _>::new(&s[..], 16).collect::<Vec<_>>();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].0, "read1 desc");
+        assert_eq!(records[0].1, PaddedBytes::from_bytes::<NucMatrix>(b"ACGT", 16));
+        assert_eq!(records[0].2, vec![40, 40, 2, 20]);
+        assert_eq!(records[1].0, "read2");
+        assert_eq!(records[1].1.len(), 2);
+        assert_eq!(records[1].2, vec![0, 0]);
+    }
+}
@@ -4,7 +4,8 @@
 //!
 //! Nucleotide and arbitrary byte alignment do not have bindings yet.
 
-use std::ffi::c_void;
+use std::ffi::{c_void, CString};
+use std::os::raw::c_char;
 
 use crate::scan_block::*;
 use crate::scores::*;
@@ -401,3 +402,68 @@ gen_functions!(
     "Frees the block used for X-drop alignment of two amino acid strings, with traceback.",
     AAMatrix, AAProfile, true, true
 );
+
+
+// Simple alignment
+
+/// Globally align two amino acid strings with traceback, without creating any handles.
+///
+/// The score is stored in `score` and the CIGAR string (with `=` and `X`) is stored in
+/// `cigar` as a null-terminated string, which must be freed with `block_free_cigar_str`.
+/// Returns false without storing anything if the CPU does not support the SIMD instruction set
+/// or if the arguments are invalid: null pointers, gap costs that are not negative or where gap
+/// open does not cost more than gap extend, block sizes that are not powers of two between
+/// 1 and 2^15, or bytes that are not letters.
+#[no_mangle]
+pub unsafe extern "C" fn block_align_str_aa(q: *const u8,
+                                            q_len: usize,
+                                            r: *const u8,
+                                            r_len: usize,
+                                            m: *const AAMatrix,
+                                            g: Gaps,
+                                            s: SizeRange,
+                                            score: *mut i32,
+                                            cigar: *mut *mut c_char) -> bool {
+    if !simd_supported() {
+        return false;
+    }
+
+    // check everything that would otherwise panic, since panics abort
+    if q.is_null() || r.is_null() || m.is_null() || score.is_null() || cigar.is_null() {
+        return false;
+    }
+    if !(g.open < g.extend && g.extend < 0) {
+        return false;
+    }
+    let valid_size = |size: usize| size.is_power_of_two() && size < (u16::MAX as usize);
+    if !valid_size(s.min) || !valid_size(s.max) || s.min > s.max {
+        return false;
+    }
+
+    let q = std::slice::from_raw_parts(q, q_len);
+    let r = std::slice::from_raw_parts(r, r_len);
+    if !q.iter().chain(r.iter()).all(|&c| AAMatrix::is_valid_char(c)) {
+        return false;
+    }
+    let m = &*m;
+
+    let q = PaddedBytes::from_bytes::<AAMatrix>(q, s.max);
+    let r = PaddedBytes::from_bytes::<AAMatrix>(r, s.max);
+    let mut a = Block::<true, false>::new(q.len(), r.len(), s.max);
+    a.align(&q, &r, m, g, s.min..=s.max, 0);
+    let res = a.res();
+
+    let mut c = Cigar::new(res.query_idx, res.reference_idx);
+    a.trace().cigar_eq(&q, &r, res.query_idx, res.reference_idx, &mut c);
+
+    *score = res.score;
+    // CIGAR strings never contain null bytes
+    *cigar = CString::new(c.to_string()).unwrap().into_raw();
+    true
+}
+
+/// Frees a CIGAR string from `block_align_str_aa`.
+#[no_mangle]
+pub unsafe extern "C" fn block_free_cigar_str(cigar: *mut c_char) {
+    drop(CString::from_raw(cigar));
+}
@@ -0,0 +1,102 @@
+//! Scalar reference implementation of global alignment restricted to a fixed band around
+//! the main diagonal, for checking `Block::align_kband` in tests.
+
+use crate::scores::*;
+use crate::scan_block::KBandResult;
+
+use alloc::vec;
+
+const NEG: i32 = i32::MIN / 2;
+
+/// Globally align two sequences, only computing cells where `|i - j| <= k`.
+///
+/// This uses dynamic programming with 32-bit scores in `O((|q| + |r|) * k)` time and
+/// `O(|r|)` space. Unlike `Block::align_kband`, the band is exactly `k` wide and this takes
+/// the original bytes.
+///
+/// Returns `None` if the end corner is outside the band, that is, if the lengths of the
+/// query and the reference differ by more than `k`.
+#[allow(non_snake_case)]
+pub fn align_kband<M: Matrix>(query: &[u8], reference: &[u8], matrix: &M, gaps: Gaps, k: usize) -> Option<KBandResult> {
+    let (n, m) = (query.len(), reference.len());
+    if n.abs_diff(m) > k {
+        return None;
+    }
+
+    let open = gaps.open as i32;
+    let extend = gaps.extend as i32;
+    // whether a cell is on the edge of the band, next to a cell that is outside the band
+    let edge = |i: usize, j: usize| (j == i + k && j < m) || (i == j + k && i < n);
+
+    // each cell stores the score and whether the best path to it touches the edge of the band
+    let mut prev = vec![(NEG, false); m + 1];
+    let mut curr = vec![(NEG, false); m + 1];
+    // scores for gaps in the reference, from the previous row
+    let mut F = vec![(NEG, false); m + 1];
+
+    prev[0] = (0, false);
+    for (j, cell) in prev.iter_mut().enumerate().take(k.min(m) + 1).skip(1) {
+        *cell = (open + extend * ((j as i32) - 1), edge(0, j));
+    }
+
+    for i in 1..=n {
+        let lo = i.saturating_sub(k);
+        let hi = (i + k).min(m);
+        // scores for gaps in the query, from the previous column
+        let mut E = (NEG, false);
+
+        if lo == 0 {
+            curr[0] = (open + extend * ((i as i32) - 1), edge(i, 0));
+        } else {
+            curr[lo - 1] = (NEG, false);
+        }
+
+        for j in lo.max(1)..=hi {
+            let e = edge(i, j);
+            E = max_flag((E.0 + extend, E.1), (curr[j - 1].0 + open, curr[j - 1].1));
+            E.1 |= e;
+            F[j] = max_flag((F[j].0 + extend, F[j].1), (prev[j].0 + open, prev[j].1));
+            F[j].1 |= e;
+            let diag = (prev[j - 1].0 + (matrix.get(query[i - 1], reference[j - 1]) as i32), prev[j - 1].1 | e);
+            curr[j] = max_flag(max_flag(diag, E), F[j]);
+        }
+
+        core::mem::swap(&mut prev, &mut curr);
+    }
+
+    let (score, touches_edge) = prev[m];
+    Some(KBandResult { score, touches_edge })
+}
+
+#[inline]
+fn max_flag(a: (i32, bool), b: (i32, bool)) -> (i32, bool) {
+    if b.0 > a.0 { b } else { a }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align_kband() {
+        let gaps = Gaps { open: -2, extend: -1 };
+
+        let res = align_kband(b"ACGTACGT", b"ACGAACGT", &NW1, gaps, 2).unwrap();
+        assert_eq!(res, KBandResult { score: 6, touches_edge: false });
+
+        let res = align_kband(b"AAAACGT", b"ACGT", &NW1, gaps, 3).unwrap();
+        assert_eq!(res.score, 4 - 4);
+
+        // the optimal path needs to go outside the band
+        let matrix = NucMatrix::new_simple(1, -4);
+        let (q, r) = (b"TTTTACGTGCATGCCA", b"ACGTGCATGCCATTTT");
+        let res = align_kband(q, r, &matrix, gaps, 2).unwrap();
+        assert!(res.touches_edge);
+        assert!(res.score < 12 - 10);
+        let res = align_kband(q, r, &matrix, gaps, 8).unwrap();
+        assert_eq!(res, KBandResult { score: 12 - 10, touches_edge: false });
+
+        assert!(align_kband(b"AAAAAA", b"AA", &NW1, gaps, 3).is_none());
+        assert_eq!(align_kband(b"", b"", &NW1, gaps, 0).unwrap().score, 0);
+    }
+}
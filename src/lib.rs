@@ -2,7 +2,8 @@
 //! gap penalty sequence-to-sequence or sequence-to-profile alignments
 //! using an adaptive block-based algorithm.
 //!
-//! Currently, SSE2, AVX2, Neon, and WASM SIMD are supported.
+//! Currently, SSE2, AVX2, AVX-512, Neon, and WASM SIMD are supported. There is also a slow,
+//! portable scalar implementation for other targets.
 //!
 //! ## Example
 //! ```
@@ -45,9 +46,30 @@
 //! Let me know how block aligner performs on your data!
 //!
 //! When building your code that uses this library, it is important to specify the
-//! correct feature flags: `simd_sse2`, `simd_avx2`, `simd_neon`, or `simd_wasm`.
+//! correct feature flags: `simd_sse2`, `simd_avx2`, `simd_avx512`, `simd_neon`, `simd_wasm`, or `simd_scalar`.
 //! More information on specifying different features for different platforms
 //! with the same dependency [here](https://doc.rust-lang.org/cargo/reference/specifying-dependencies.html#platform-specific-dependencies).
+//!
+//! The optional `rayon` feature enables `batch::align_batch` for aligning many pairs of
+//! sequences on multiple threads.
+//!
+//! The optional `python` feature enables Python bindings through PyO3. See the `python`
+//! directory for how to build the Python module.
+//!
+//! The optional `wasm` feature enables JavaScript bindings through wasm-bindgen. See the `wasm`
+//! directory for how to build the WASM module.
+//!
+//! The optional `serde` feature enables serializing and deserializing `scores::AAProfile`,
+//! so large position-specific scoring matrices can be cached instead of parsed every time.
+//!
+//! The `no_std` feature builds the library without the standard library, so only `core` and
+//! `alloc` are required. The `cache`, `fastq`, `dotplot`, `batch`, and C API modules and
+//! `scores::AAProfile::from_msa` are not available with `no_std`, and `scan_block::simd_supported` only checks the target features
+//! that are enabled at compile time.
+
+#![cfg_attr(all(feature = "no_std", not(test)), no_std)]
+
+extern crate alloc;
 
 // special SIMD instruction set modules adapted for this library
 // their types and lengths are abstracted out
@@ -88,18 +110,57 @@ pub mod neon;
 #[cfg(feature = "simd_neon")]
 pub use neon::L;
 
-#[cfg(any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon"))]
+#[cfg(feature = "simd_avx512")]
+#[macro_use]
+#[doc(hidden)]
+/// cbindgen:ignore
+pub mod avx512;
+
+#[cfg(feature = "simd_avx512")]
+pub use avx512::L;
+
+#[cfg(feature = "simd_scalar")]
+#[macro_use]
+#[doc(hidden)]
+/// cbindgen:ignore
+pub mod scalar;
+
+#[cfg(feature = "simd_scalar")]
+pub use scalar::L;
+
+#[cfg(any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon", feature = "simd_avx512", feature = "simd_scalar"))]
 pub mod scan_block;
-#[cfg(any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon"))]
+#[cfg(any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon", feature = "simd_avx512", feature = "simd_scalar"))]
 pub mod scores;
-#[cfg(any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon"))]
+#[cfg(any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon", feature = "simd_avx512", feature = "simd_scalar"))]
 pub mod cigar;
+#[cfg(any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon", feature = "simd_avx512", feature = "simd_scalar"))]
+pub mod multi;
+#[cfg(all(not(feature = "no_std"), any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon", feature = "simd_avx512", feature = "simd_scalar")))]
+pub mod cache;
+#[cfg(all(not(feature = "no_std"), any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon", feature = "simd_avx512", feature = "simd_scalar")))]
+pub mod fastq;
+#[cfg(any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon", feature = "simd_avx512", feature = "simd_scalar"))]
+pub mod anchored;
+#[cfg(all(test, any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon", feature = "simd_avx512", feature = "simd_scalar")))]
+mod kband;
+#[cfg(all(feature = "rayon", not(feature = "no_std"), any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon", feature = "simd_avx512", feature = "simd_scalar")))]
+pub mod batch;
 
-#[cfg(any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon"))]
+#[cfg(all(not(feature = "no_std"), any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon", feature = "simd_avx512", feature = "simd_scalar")))]
 #[doc(hidden)]
 pub mod ffi;
 
-#[cfg(not(any(feature = "no_simd", feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon")))]
+#[cfg(all(feature = "python", not(feature = "no_std"), any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon", feature = "simd_avx512", feature = "simd_scalar")))]
+mod python;
+
+#[cfg(all(feature = "wasm", not(feature = "no_std"), any(feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon", feature = "simd_avx512", feature = "simd_scalar")))]
+pub mod wasm;
+
+#[cfg(not(feature = "no_std"))]
+pub mod dotplot;
+
+#[cfg(not(any(feature = "no_simd", feature = "simd_sse2", feature = "simd_avx2", feature = "simd_wasm", feature = "simd_neon", feature = "simd_avx512", feature = "simd_scalar")))]
 compile_error!("No SIMD feature flag specified! Specify \"no_simd\" to disable all SIMD features.");
 
 /// Calculate the percentage of a length, rounded to the next power of two.
@@ -107,5 +168,6 @@ compile_error!("No SIMD feature flag specified! Specify \"no_simd\" to disable a
 /// This is useful for computing the min and max block sizes for sequences of a certain
 /// length by using percentages. The returned value is at least 32 and at most 16384.
 pub fn percent_len(len: usize, p: f32) -> usize {
-    ((p * (len as f32)).round() as usize).max(32).next_power_of_two().min(1 << 14)
+    // f32::round is not available in core, but the value is never negative
+    ((p * (len as f32) + 0.5) as usize).max(32).next_power_of_two().min(1 << 14)
 }
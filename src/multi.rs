@@ -0,0 +1,176 @@
+//! Inter-sequence SIMD alignment of one query against multiple references at once.
+//!
+//! Instead of vectorizing the DP matrix of a single alignment like the block aligner,
+//! each 16-bit SIMD lane computes the DP matrix for a different reference.
+//! This is much faster than aligning each pair separately when there are many short
+//! sequences (for example, peptides or short reads) that are too small to fill a block.
+
+#[cfg(feature = "simd_sse2")]
+use crate::sse2::*;
+
+#[cfg(feature = "simd_avx2")]
+use crate::avx2::*;
+
+#[cfg(feature = "simd_wasm")]
+use crate::simd128::*;
+
+#[cfg(feature = "simd_neon")]
+use crate::neon::*;
+
+#[cfg(feature = "simd_avx512")]
+use crate::avx512::*;
+
+#[cfg(feature = "simd_scalar")]
+use crate::scalar::*;
+
+use crate::scores::*;
+use crate::scan_block::*;
+
+use alloc::vec::Vec;
+use alloc::vec;
+
+/// Globally align a query against `L` references simultaneously, with one reference
+/// in each SIMD lane.
+///
+/// The entire DP matrix is computed for every reference, so the scores are exact.
+/// The references can have different lengths; the DP matrix is computed up to the length
+/// of the longest reference and the score for each reference is taken from its own last
+/// column.
+///
+/// The `block_size` used to create the padded strings does not matter, since only the
+/// bytes in each string are read.
+///
+/// Only the score is computed (no traceback). Scores are stored as 16-bit deltas from an
+/// offset that depends on the SIMD instruction set (`2^14` for most), so this is only
+/// accurate when every DP cell fits in that range. This is true for short sequences,
+/// but long sequences should be aligned with `Block` instead.
+pub fn striped_align<M: Matrix>(query: &PaddedBytes, refs: &[PaddedBytes; L], matrix: &M, gaps: Gaps) -> [AlignResult; L] {
+    assert!(gaps.open < 0 && gaps.extend < 0, "Gap costs must be negative!");
+    assert!(gaps.open < gaps.extend, "Gap open must cost more than gap extend!");
+    assert!(refs.iter().all(|r| r.len() < (i16::MAX as usize)), "Reference lengths must be smaller than 2^15 - 1!");
+
+    let max_len = refs.iter().map(|r| r.len()).max().unwrap();
+
+    // transpose the references so the j-th byte of every reference can be loaded
+    // with a single load; the end is padded to allow loading an entire HalfSimd
+    let mut refs_t = vec![M::convert_char(M::NULL); (max_len + 1) * L + L * HALFSIMD_MUL];
+    for (k, r) in refs.iter().enumerate() {
+        for j in 1..=r.len() {
+            refs_t[j * L + k] = unsafe { r.get(j) };
+        }
+    }
+
+    let mut lens = [0i16; L];
+    for (k, r) in refs.iter().enumerate() {
+        lens[k] = r.len() as i16;
+    }
+
+    let scores = unsafe { striped_align_core(query, &refs_t, &lens, max_len, matrix, gaps) };
+
+    let mut res = [AlignResult { score: 0, query_idx: query.len(), reference_idx: 0 }; L];
+    for k in 0..L {
+        res[k].score = (scores[k] as i32) - (ZERO as i32);
+        res[k].reference_idx = refs[k].len();
+    }
+    res
+}
+
+#[cfg_attr(feature = "simd_sse2", target_feature(enable = "sse2"))]
+#[cfg_attr(feature = "simd_avx2", target_feature(enable = "avx2"))]
+#[cfg_attr(feature = "simd_wasm", target_feature(enable = "simd128"))]
+#[cfg_attr(feature = "simd_neon", target_feature(enable = "neon"))]
+#[cfg_attr(feature = "simd_avx512", target_feature(enable = "avx512bw"))]
+#[allow(non_snake_case)]
+unsafe fn striped_align_core<M: Matrix>(query: &PaddedBytes, refs_t: &[u8], lens: &[i16; L], max_len: usize, matrix: &M, gaps: Gaps) -> [i16; L] {
+    let gap_open = simd_set1_i16(gaps.open as i16);
+    let gap_extend = simd_set1_i16(gaps.extend as i16);
+    let min = simd_set1_i16(MIN);
+
+    #[repr(align(64))]
+    struct A([i16; L]);
+
+    let lens_a = A(*lens);
+    let lens_v = simd_load(lens_a.0.as_ptr() as *const Simd);
+
+    // D and C columns for the previous reference position, indexed by query position
+    let mut D_col = Vec::with_capacity(query.len() + 1);
+    let mut C_col = vec![min; query.len() + 1];
+
+    // first column: only vertical gaps
+    D_col.push(simd_set1_i16(ZERO));
+    let mut D = simd_adds_i16(simd_set1_i16(ZERO), gap_open);
+    for _ in 1..=query.len() {
+        D_col.push(D);
+        D = simd_adds_i16(D, gap_extend);
+    }
+
+    // capture the last row for references of length zero
+    let mut res = simd_blend_i8(min, D_col[query.len()], simd_cmpeq_i16(lens_v, simd_set1_i16(0)));
+
+    for j in 1..=max_len {
+        let r = halfsimd_loadu(refs_t.as_ptr().add(j * L) as _);
+
+        // first row: only horizontal gaps
+        let mut D00 = D_col[0];
+        let C = simd_max_i16(simd_adds_i16(C_col[0], gap_extend), simd_adds_i16(D00, gap_open));
+        C_col[0] = C;
+        D_col[0] = C;
+
+        let mut D10 = C;
+        let mut R = min;
+
+        for i in 1..=query.len() {
+            let scores = matrix.get_scores(query.get(i), r, false);
+            let D01 = D_col[i];
+            let C = simd_max_i16(simd_adds_i16(C_col[i], gap_extend), simd_adds_i16(D01, gap_open));
+            R = simd_max_i16(simd_adds_i16(R, gap_extend), simd_adds_i16(D10, gap_open));
+            let D11 = simd_max_i16(simd_adds_i16(D00, scores), simd_max_i16(C, R));
+
+            C_col[i] = C;
+            D_col[i] = D11;
+            D00 = D01;
+            D10 = D11;
+        }
+
+        let mask = simd_cmpeq_i16(lens_v, simd_set1_i16(j as i16));
+        res = simd_blend_i8(res, D_col[query.len()], mask);
+    }
+
+    let mut res_a = A([0i16; L]);
+    simd_store(res_a.0.as_mut_ptr() as *mut Simd, res);
+    res_a.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_striped_align() {
+        let seqs = [
+            &b"AARA"[..], b"AAAA", b"", b"RRRRRRRRR", b"AARAAAAARAAA", b"ARR", b"A", b"AAAAAAAAAAAAAAAAAAAAAAAA",
+            b"AARA", b"RA", b"RRAAR", b"RRRRRRRRRRRRRRRRR", b"AAAAAAAAAAAAAAAAAAAAAAAAAAA", b"AARRA", b"R", b"ARARARARARARA"
+        ];
+        let q_bytes = b"AARRAAA";
+        let gaps = Gaps { open: -11, extend: -1 };
+
+        let q = PaddedBytes::from_bytes::<AAMatrix>(q_bytes, 32);
+        let refs: [PaddedBytes; L] = std::array::from_fn(|k| PaddedBytes::from_bytes::<AAMatrix>(seqs[k % seqs.len()], 32));
+        let res = striped_align(&q, &refs, &BLOSUM62, gaps);
+
+        for k in 0..L {
+            let seq = seqs[k % seqs.len()];
+            assert_eq!(res[k].query_idx, q_bytes.len());
+            assert_eq!(res[k].reference_idx, seq.len());
+
+            if seq.is_empty() {
+                assert_eq!(res[k].score, -11 - 6);
+                continue;
+            }
+
+            let mut a = Block::<false, false>::new(q.len(), refs[k].len(), 32);
+            a.align(&q, &refs[k], &BLOSUM62, gaps, 32..=32, 0);
+            assert_eq!(res[k].score, a.res().score);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::arch::aarch64::*;
+use core::arch::aarch64::*;
 
 pub type Simd = int16x8_t;
 pub type HalfSimd = int8x8_t;
@@ -67,7 +67,7 @@ macro_rules! simd_extract_i16 {
         {
             debug_assert!($num < L);
             #[cfg(target_arch = "aarch64")]
-            use std::arch::aarch64::*;
+            use core::arch::aarch64::*;
             vgetq_lane_s16($a, $num as i32)
         }
     };
@@ -80,7 +80,7 @@ macro_rules! simd_insert_i16 {
         {
             debug_assert!($num < L);
             #[cfg(target_arch = "aarch64")]
-            use std::arch::aarch64::*;
+            use core::arch::aarch64::*;
             vsetq_lane_s16($v, $a, $num as i32)
         }
     };
@@ -108,7 +108,7 @@ macro_rules! simd_sl_i16 {
         {
             debug_assert!($num <= L);
             #[cfg(target_arch = "aarch64")]
-            use std::arch::aarch64::*;
+            use core::arch::aarch64::*;
             vextq_s16($b, $a, (L - $num) as i32)
         }
     };
@@ -121,7 +121,7 @@ macro_rules! simd_sr_i16 {
         {
             debug_assert!($num <= L);
             #[cfg(target_arch = "aarch64")]
-            use std::arch::aarch64::*;
+            use core::arch::aarch64::*;
             if $num == L {
                 $a
             } else {
@@ -351,13 +351,14 @@ macro_rules! halfsimd_sr_i8 {
         {
             debug_assert!($num <= L);
             #[cfg(target_arch = "aarch64")]
-            use std::arch::aarch64::*;
+            use core::arch::aarch64::*;
             vext_s8($b, $a, $num as i32)
         }
     };
 }
 
 #[target_feature(enable = "neon")]
+#[cfg(not(feature = "no_std"))]
 #[allow(dead_code)]
 pub unsafe fn simd_dbg_i16(v: Simd) {
     #[repr(align(16))]
@@ -373,6 +374,7 @@ pub unsafe fn simd_dbg_i16(v: Simd) {
 }
 
 #[target_feature(enable = "neon")]
+#[cfg(not(feature = "no_std"))]
 #[allow(dead_code)]
 pub unsafe fn halfsimd_dbg_i8(v: HalfSimd) {
     #[repr(align(8))]
@@ -0,0 +1,78 @@
+//! Python bindings for block aligner.
+//!
+//! The extension module is built from the `python` directory with maturin.
+
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+
+use crate::scan_block::*;
+use crate::scores::*;
+use crate::cigar::*;
+
+fn align_with<M: Matrix>(query: &[u8], reference: &[u8], matrix: &M, gaps: Gaps, min_size: usize, max_size: usize) -> PyResult<(i32, String)> {
+    check_bytes::<M>(query, "query")?;
+    check_bytes::<M>(reference, "reference")?;
+
+    let q = PaddedBytes::from_bytes::<M>(query, max_size);
+    let r = PaddedBytes::from_bytes::<M>(reference, max_size);
+    let mut a = Block::<true, false>::new(q.len(), r.len(), max_size);
+    a.align(&q, &r, matrix, gaps, min_size..=max_size, 0);
+    let res = a.res();
+
+    let mut cigar = Cigar::new(res.query_idx, res.reference_idx);
+    a.trace().cigar(res.query_idx, res.reference_idx, &mut cigar);
+    Ok((res.score, cigar.to_sam_string(false, (0, 0))))
+}
+
+fn check_bytes<M: Matrix>(b: &[u8], name: &str) -> PyResult<()> {
+    match b.iter().position(|&c| !M::is_valid_char(c)) {
+        Some(idx) => Err(PyValueError::new_err(format!("Invalid character {:?} at index {} in the {}", b[idx] as char, idx, name))),
+        None => Ok(())
+    }
+}
+
+/// Globally align a query and a reference, returning the score and the SAM CIGAR string.
+///
+/// `matrix` is the name of a built-in scoring matrix, like `"BLOSUM62"` or `"NW1"`.
+/// A gap of length n costs `gap_open + gap_extend * (n - 1)`, so both must be negative
+/// and `gap_open` must be less than `gap_extend`.
+/// Raises `ValueError` if a character is not supported by the scoring matrix.
+#[pyfunction]
+fn align(query: &str, reference: &str, matrix: &str, gap_open: i8, gap_extend: i8, min_size: usize, max_size: usize) -> PyResult<(i32, String)> {
+    if !(gap_open < gap_extend && gap_extend < 0) {
+        return Err(PyValueError::new_err("Gap costs must be negative and gap open must cost more than gap extend!"));
+    }
+    if !min_size.is_power_of_two() || !max_size.is_power_of_two() || min_size > max_size {
+        return Err(PyValueError::new_err("Block sizes must be powers of two and the min size must not be greater than the max size!"));
+    }
+    if !simd_supported() {
+        return Err(PyValueError::new_err("The SIMD instruction set is not supported by this CPU!"));
+    }
+
+    let gaps = Gaps { open: gap_open, extend: gap_extend };
+    let (q, r) = (query.as_bytes(), reference.as_bytes());
+    let aa = |m: &AAMatrix| align_with(q, r, m, gaps, min_size, max_size);
+
+    match matrix {
+        "NW1" => align_with(q, r, &NW1, gaps, min_size, max_size),
+        "NUC_IDENTITY" => align_with(q, r, &NUC_IDENTITY, gaps, min_size, max_size),
+        "AA_IDENTITY" => aa(&AA_IDENTITY),
+        "BLOSUM45" => aa(&BLOSUM45),
+        "BLOSUM50" => aa(&BLOSUM50),
+        "BLOSUM62" => aa(&BLOSUM62),
+        "BLOSUM80" => aa(&BLOSUM80),
+        "BLOSUM90" => aa(&BLOSUM90),
+        "PAM100" => aa(&PAM100),
+        "PAM120" => aa(&PAM120),
+        "PAM160" => aa(&PAM160),
+        "PAM200" => aa(&PAM200),
+        "PAM250" => aa(&PAM250),
+        _ => Err(PyValueError::new_err(format!("Unknown scoring matrix: {}", matrix)))
+    }
+}
+
+#[pymodule]
+fn block_aligner(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(align, m)?)?;
+    Ok(())
+}
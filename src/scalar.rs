@@ -0,0 +1,394 @@
+// Portable implementation of the SIMD functions with plain arrays.
+// This is much slower than the real SIMD instruction sets, but it runs on any target and
+// it is useful as a reference for testing the other implementations.
+// Bytes within each 16-bit element are ordered like on x86 (little endian).
+
+/// Vector of 16-bit elements.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C, align(32))]
+pub struct I16Vec(pub [i16; L]);
+
+/// Vector of bytes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C, align(16))]
+pub struct I8Vec(pub [i8; L]);
+
+pub type Simd = I16Vec; // use for storing DP scores
+pub type HalfSimd = I8Vec; // used for storing bytes (sequence or scoring matrix)
+pub type LutSimd = I8Vec; // used for storing a row in a scoring matrix (always 128 bits)
+pub type TraceType = i32;
+/// Number of 16-bit lanes in a SIMD vector.
+pub const L: usize = 16;
+pub const L_BYTES: usize = L * 2;
+pub const HALFSIMD_MUL: usize = 1;
+// using min = 0 is faster, but restricts range of scores (and restricts the max block size)
+pub const ZERO: i16 = 1 << 14;
+pub const MIN: i16 = 0;
+
+#[inline]
+fn map2(a: Simd, b: Simd, f: impl Fn(i16, i16) -> i16) -> Simd {
+    let mut res = a;
+    for i in 0..L {
+        res.0[i] = f(a.0[i], b.0[i]);
+    }
+    res
+}
+
+#[inline]
+pub unsafe fn store_trace(ptr: *mut TraceType, trace: TraceType) { *ptr = trace; }
+
+#[inline]
+pub unsafe fn simd_adds_i16(a: Simd, b: Simd) -> Simd { map2(a, b, i16::saturating_add) }
+
+#[inline]
+pub unsafe fn simd_subs_i16(a: Simd, b: Simd) -> Simd { map2(a, b, i16::saturating_sub) }
+
+#[inline]
+pub unsafe fn simd_max_i16(a: Simd, b: Simd) -> Simd { map2(a, b, i16::max) }
+
+#[inline]
+pub unsafe fn simd_cmpeq_i16(a: Simd, b: Simd) -> Simd { map2(a, b, |x, y| if x == y { -1 } else { 0 }) }
+
+#[inline]
+pub unsafe fn simd_cmpgt_i16(a: Simd, b: Simd) -> Simd { map2(a, b, |x, y| if x > y { -1 } else { 0 }) }
+
+// only the most significant bit of each byte in the mask matters
+#[inline]
+pub unsafe fn simd_blend_i8(a: Simd, b: Simd, mask: Simd) -> Simd {
+    let mut res = a;
+    for i in 0..L {
+        let lo = if mask.0[i] & 0x0080 != 0 { b.0[i] } else { a.0[i] };
+        let hi = if mask.0[i] < 0 { b.0[i] } else { a.0[i] };
+        res.0[i] = ((hi as u16 & 0xFF00) | (lo as u16 & 0x00FF)) as i16;
+    }
+    res
+}
+
+#[inline]
+pub unsafe fn simd_load(ptr: *const Simd) -> Simd { core::ptr::read(ptr) }
+
+#[inline]
+pub unsafe fn simd_loadu(ptr: *const Simd) -> Simd { core::ptr::read_unaligned(ptr) }
+
+#[inline]
+pub unsafe fn simd_store(ptr: *mut Simd, a: Simd) { core::ptr::write(ptr, a) }
+
+#[inline]
+pub unsafe fn simd_set1_i16(v: i16) -> Simd { I16Vec([v; L]) }
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! simd_extract_i16 {
+    ($a:expr, $num:expr) => {
+        {
+            debug_assert!($num < L);
+            $a.0[$num]
+        }
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! simd_insert_i16 {
+    ($a:expr, $v:expr, $num:expr) => {
+        {
+            debug_assert!($num < L);
+            let mut a = $a;
+            a.0[$num] = $v;
+            a
+        }
+    };
+}
+
+#[inline]
+pub unsafe fn simd_movemask_i8(a: Simd) -> u32 {
+    let mut res = 0u32;
+    for i in 0..L {
+        res |= (((a.0[i] as u16 >> 7) & 1) as u32) << (i * 2);
+        res |= (((a.0[i] as u16 >> 15) & 1) as u32) << (i * 2 + 1);
+    }
+    res
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! simd_sl_i16 {
+    ($a:expr, $b:expr, $num:expr) => {
+        {
+            debug_assert!(2 * $num <= L);
+            let a = $a;
+            let b = $b;
+            let mut res = a;
+            for i in 0..L {
+                res.0[i] = if i >= $num { a.0[i - $num] } else { b.0[L - $num + i] };
+            }
+            res
+        }
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! simd_sr_i16 {
+    ($a:expr, $b:expr, $num:expr) => {
+        {
+            debug_assert!(2 * $num <= L);
+            let a = $a;
+            let b = $b;
+            let mut res = a;
+            for i in 0..L {
+                res.0[i] = if i < L - $num { b.0[i + $num] } else { a.0[i - (L - $num)] };
+            }
+            res
+        }
+    };
+}
+
+// hardcoded to STEP = 8
+#[inline]
+pub unsafe fn simd_step(a: Simd, b: Simd) -> Simd {
+    simd_sr_i16!(a, b, 8)
+}
+
+// broadcast last 16-bit element to the whole vector
+#[inline]
+pub unsafe fn simd_broadcasthi_i16(v: Simd) -> Simd {
+    simd_set1_i16(v.0[L - 1])
+}
+
+#[inline]
+pub unsafe fn simd_slow_extract_i16(v: Simd, i: usize) -> i16 {
+    debug_assert!(i < L);
+    v.0[i]
+}
+
+#[inline]
+pub unsafe fn simd_hmax_i16(v: Simd) -> i16 {
+    *v.0.iter().max().unwrap()
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! simd_prefix_hadd_i16 {
+    ($a:expr, $num:expr) => {
+        {
+            debug_assert!(2 * $num <= L);
+            let a = $a;
+            let mut res = 0i16;
+            for i in 0..$num {
+                res = res.saturating_add(a.0[i].saturating_sub(ZERO));
+            }
+            res
+        }
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! simd_prefix_hmax_i16 {
+    ($a:expr, $num:expr) => {
+        {
+            debug_assert!(2 * $num <= L);
+            let a = $a;
+            *a.0[..$num].iter().max().unwrap()
+        }
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! simd_suffix_hmax_i16 {
+    ($a:expr, $num:expr) => {
+        {
+            debug_assert!(2 * $num <= L);
+            let a = $a;
+            *a.0[L - $num..].iter().max().unwrap()
+        }
+    };
+}
+
+#[inline]
+pub unsafe fn simd_hargmax_i16(v: Simd, max: i16) -> usize {
+    v.0.iter().position(|&x| x == max).unwrap_or(L)
+}
+
+#[inline]
+#[allow(non_snake_case)]
+#[allow(dead_code)]
+pub unsafe fn simd_naive_prefix_scan_i16(R_max: Simd, gap_cost: Simd, _gap_cost_lane: PrefixScanConsts) -> Simd {
+    let mut curr = R_max;
+
+    for _i in 0..(L - 1) {
+        let prev = curr;
+        curr = simd_sl_i16!(curr, simd_set1_i16(0), 1);
+        curr = simd_adds_i16(curr, gap_cost);
+        curr = simd_max_i16(curr, prev);
+    }
+
+    curr
+}
+
+pub type PrefixScanConsts = ();
+
+#[inline]
+pub unsafe fn get_prefix_scan_consts(gap: Simd) -> (Simd, PrefixScanConsts) {
+    let mut res = gap;
+    for i in 1..L {
+        res.0[i] = res.0[i - 1].saturating_add(gap.0[i]);
+    }
+    (res, ())
+}
+
+#[inline]
+#[allow(non_snake_case)]
+pub unsafe fn simd_prefix_scan_i16(R_max: Simd, gap_cost: Simd, _gap_cost_lane: PrefixScanConsts) -> Simd {
+    let mut res = R_max;
+    for i in 1..L {
+        res.0[i] = res.0[i].max(res.0[i - 1].saturating_add(gap_cost.0[i]));
+    }
+    res
+}
+
+// lookup a byte in a 128-bit table, like pshufb
+#[inline]
+fn lookup_byte(lut: LutSimd, b: i8) -> i8 {
+    if b < 0 { 0 } else { lut.0[(b & 0x0F) as usize] }
+}
+
+// lookup two 128-bit tables
+#[inline]
+pub unsafe fn halfsimd_lookup2_i16(lut1: LutSimd, lut2: LutSimd, v: HalfSimd) -> Simd {
+    let mut res = simd_set1_i16(0);
+    for i in 0..L {
+        let b = v.0[i];
+        res.0[i] = (if b & 0x10 != 0 { lookup_byte(lut2, b) } else { lookup_byte(lut1, b) }) as i16;
+    }
+    res
+}
+
+#[inline]
+pub unsafe fn halfsimd_lookup1_i16(lut: LutSimd, v: HalfSimd) -> Simd {
+    let mut res = simd_set1_i16(0);
+    for i in 0..L {
+        res.0[i] = lookup_byte(lut, v.0[i]) as i16;
+    }
+    res
+}
+
+#[inline]
+pub unsafe fn halfsimd_lookup_bytes_i16(match_scores: HalfSimd, mismatch_scores: HalfSimd, a: HalfSimd, b: HalfSimd) -> Simd {
+    let mut res = simd_set1_i16(0);
+    for i in 0..L {
+        res.0[i] = (if a.0[i] == b.0[i] { match_scores.0[i] } else { mismatch_scores.0[i] }) as i16;
+    }
+    res
+}
+
+#[inline]
+pub unsafe fn halfsimd_load(ptr: *const HalfSimd) -> HalfSimd { core::ptr::read(ptr) }
+
+#[inline]
+pub unsafe fn halfsimd_loadu(ptr: *const HalfSimd) -> HalfSimd { core::ptr::read_unaligned(ptr) }
+
+#[inline]
+pub unsafe fn lutsimd_load(ptr: *const LutSimd) -> LutSimd { core::ptr::read(ptr) }
+
+#[inline]
+pub unsafe fn lutsimd_loadu(ptr: *const LutSimd) -> LutSimd { core::ptr::read_unaligned(ptr) }
+
+#[inline]
+pub unsafe fn halfsimd_store(ptr: *mut HalfSimd, a: HalfSimd) { core::ptr::write(ptr, a) }
+
+#[inline]
+pub unsafe fn halfsimd_sub_i8(a: HalfSimd, b: HalfSimd) -> HalfSimd {
+    let mut res = a;
+    for i in 0..L {
+        res.0[i] = a.0[i].wrapping_sub(b.0[i]);
+    }
+    res
+}
+
+#[inline]
+pub unsafe fn halfsimd_set1_i8(v: i8) -> HalfSimd { I8Vec([v; L]) }
+
+#[inline]
+pub unsafe fn halfsimd_get_idx(i: usize) -> usize { i }
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! halfsimd_sr_i8 {
+    ($a:expr, $b:expr, $num:expr) => {
+        {
+            debug_assert!($num <= L);
+            let a = $a;
+            let b = $b;
+            let mut res = a;
+            for i in 0..L {
+                res.0[i] = if i < L - $num { b.0[i + $num] } else { a.0[i - (L - $num)] };
+            }
+            res
+        }
+    };
+}
+
+#[cfg(not(feature = "no_std"))]
+#[allow(dead_code)]
+pub unsafe fn simd_dbg_i16(v: Simd) {
+    for i in (0..L).rev() {
+        print!("{:6} ", v.0[i]);
+    }
+    println!();
+}
+
+#[cfg(not(feature = "no_std"))]
+#[allow(dead_code)]
+pub unsafe fn halfsimd_dbg_i8(v: HalfSimd) {
+    for i in (0..L).rev() {
+        print!("{:3} ", v.0[i]);
+    }
+    println!();
+}
+
+#[allow(dead_code)]
+pub unsafe fn simd_assert_vec_eq(a: Simd, b: [i16; L]) {
+    assert_eq!(a.0, b);
+}
+
+#[allow(dead_code)]
+pub unsafe fn halfsimd_assert_vec_eq(a: HalfSimd, b: [i8; L]) {
+    assert_eq!(a.0, b);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_scan() {
+        unsafe {
+            let vec = I16Vec([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 15, 12, 13, 14, 11]);
+            let gap = simd_set1_i16(0);
+            let (_, consts) = get_prefix_scan_consts(gap);
+            let res = simd_prefix_scan_i16(vec, gap, consts);
+            simd_assert_vec_eq(res, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 15, 15, 15, 15, 15]);
+
+            let gap = simd_set1_i16(-1);
+            let (_, consts) = get_prefix_scan_consts(gap);
+            let res = simd_prefix_scan_i16(vec, gap, consts);
+            simd_assert_vec_eq(res, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 15, 14, 13, 14, 13]);
+            assert_eq!(res, simd_naive_prefix_scan_i16(vec, gap, consts));
+        }
+    }
+
+    #[test]
+    fn test_movemask_blend() {
+        unsafe {
+            let a = simd_set1_i16(0x1234);
+            let b = simd_set1_i16(0x5678);
+            let mask = simd_insert_i16!(simd_set1_i16(0), 0xFF00u16 as i16, 1);
+            assert_eq!(simd_extract_i16!(simd_blend_i8(a, b, mask), 1), 0x5634);
+            assert_eq!(simd_extract_i16!(simd_blend_i8(a, b, mask), 0), 0x1234);
+            assert_eq!(simd_movemask_i8(mask), 0b1000);
+        }
+    }
+}
@@ -12,7 +12,17 @@ use crate::simd128::*;
 #[cfg(feature = "simd_neon")]
 use crate::neon::*;
 
-use std::i8;
+#[cfg(feature = "simd_avx512")]
+use crate::avx512::*;
+
+#[cfg(feature = "simd_scalar")]
+use crate::scalar::*;
+
+use core::{i8, fmt};
+use alloc::vec::Vec;
+use alloc::vec;
+use alloc::string::String;
+use alloc::borrow::ToOwned;
 
 pub trait Matrix {
     /// Byte to use as padding.
@@ -32,11 +42,20 @@ pub trait Matrix {
     /// Convert a byte to a better storage format that makes retrieving scores
     /// easier.
     fn convert_char(c: u8) -> u8;
+    /// Check whether a byte is supported by `convert_char`.
+    ///
+    /// By default, bytes from `A` up to (but not including) `NULL` are supported, and lowercase
+    /// characters are uppercased.
+    fn is_valid_char(c: u8) -> bool {
+        let c = c.to_ascii_uppercase();
+        c >= b'A' && c < Self::NULL
+    }
 }
 
 /// Amino acid scoring matrix.
 ///
 /// Supports characters `A` to `Z`. Lowercase characters are uppercased.
+/// Use `with_x_score` to change the scores for `X`.
 #[repr(C, align(32))]
 #[derive(Clone, PartialEq, Debug)]
 pub struct AAMatrix {
@@ -60,6 +79,51 @@ impl AAMatrix {
         Self { scores }
     }
 
+    /// Create a nucleotide matrix that supports IUPAC ambiguity codes.
+    ///
+    /// A pair of characters gets `match_score` if they can represent the same base, and
+    /// `mismatch_score` otherwise. For example, `R` (`A` or `G`) against `A` is a match,
+    /// which is the max score over the bases that the codes represent.
+    /// `U` is treated as `T`, and characters that are not IUPAC codes always mismatch.
+    ///
+    /// `NucMatrix` cannot be used for this since it only supports `A`, `C`, `G`, `N`, and `T`.
+    pub const fn new_iupac(match_score: i8, mismatch_score: i8) -> Self {
+        // bit masks for A, C, G, and T
+        const fn bases(c: u8) -> u8 {
+            match c {
+                b'A' => 0b0001,
+                b'C' => 0b0010,
+                b'G' => 0b0100,
+                b'T' | b'U' => 0b1000,
+                b'R' => 0b0101,
+                b'Y' => 0b1010,
+                b'S' => 0b0110,
+                b'W' => 0b1001,
+                b'K' => 0b1100,
+                b'M' => 0b0011,
+                b'B' => 0b1110,
+                b'D' => 0b1101,
+                b'H' => 0b1011,
+                b'V' => 0b0111,
+                b'N' => 0b1111,
+                _ => 0
+            }
+        }
+
+        let mut scores = [i8::MIN; 27 * 32];
+        let mut i = b'A';
+        while i <= b'Z' {
+            let mut j = b'A';
+            while j <= b'Z' {
+                let idx = ((i - b'A') as usize) * 32 + ((j - b'A') as usize);
+                scores[idx] = if (bases(i) & bases(j)) > 0 { match_score } else { mismatch_score };
+                j += 1;
+            }
+            i += 1;
+        }
+        Self { scores }
+    }
+
     /// Create an AAMatrix from a tab-separated table with no headers.
     ///
     /// Use `aa_order` to pass in the amino acids in order.
@@ -77,8 +141,188 @@ impl AAMatrix {
 
         res
     }
+
+    /// Create an AAMatrix from a matrix in the NCBI text format.
+    ///
+    /// This is the format used for BLOSUM and PAM matrices distributed by NCBI: lines starting
+    /// with `#` are comments, the first other line is a header with the amino acids, and each
+    /// following line starts with an amino acid and then has one score for each amino acid in
+    /// the header. Rows and columns for `*` are skipped since they cannot be represented.
+    ///
+    /// Returns an error if the text is malformed or if the matrix is not symmetric.
+    pub fn from_ncbi_str(s: &str) -> Result<Self, ParseError> {
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l.trim()))
+            .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'));
+
+        let (_, header) = lines.next().ok_or(ParseError::MissingHeader)?;
+        let aa_order = header
+            .split_ascii_whitespace()
+            .map(parse_aa)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut res = Self::new();
+        let mut rows = Vec::with_capacity(aa_order.len());
+
+        for (line_num, line) in lines {
+            let mut tokens = line.split_ascii_whitespace();
+            let a = parse_aa(tokens.next().unwrap())?;
+            let scores = tokens
+                .map(|t| t.parse::<i8>().map_err(|_| ParseError::InvalidLine(line_num)))
+                .collect::<Result<Vec<_>, _>>()?;
+            if scores.len() != aa_order.len() {
+                return Err(ParseError::InvalidLine(line_num));
+            }
+            rows.push((a, scores));
+        }
+
+        for (a, scores) in &rows {
+            for (b, &score) in aa_order.iter().zip(scores) {
+                if let (Some(a), Some(b)) = (a, b) {
+                    if res.get(*a, *b) != i8::MIN && res.get(*a, *b) != score {
+                        return Err(ParseError::Asymmetric(*a as char, *b as char));
+                    }
+                    res.set(*a, *b, score);
+                }
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Create a copy of this matrix where `X` (unknown amino acid) against any amino acid,
+    /// including `X`, has a certain score.
+    ///
+    /// The built-in BLOSUM and PAM matrices use the `X` scores from the NCBI matrices, which
+    /// depend on the other amino acid (for example, between -2 and 0 in BLOSUM62).
+    pub fn with_x_score(&self, score: i8) -> Self {
+        let mut res = self.clone();
+        for c in b'A'..=b'Z' {
+            res.set(b'X', c, score);
+        }
+        res
+    }
+}
+
+/// Builder for an `AAMatrix` with scores for ambiguous amino acids filled in automatically.
+///
+/// Scores must be set for every pair of the 20 standard amino acids. The scores for `B`
+/// (`D` or `N`), `Z` (`E` or `Q`), `J` (`I` or `L`), and `X` (any standard amino acid) are the
+/// min over the amino acids they represent, unless they are explicitly set.
+#[derive(Clone, Debug)]
+pub struct AAMatrixBuilder {
+    matrix: AAMatrix,
+    is_set: [[bool; 26]; 26]
+}
+
+impl AAMatrixBuilder {
+    const STANDARD: &'static [u8] = b"ACDEFGHIKLMNPQRSTVWY";
+
+    /// Create a builder with no scores set.
+    pub fn new() -> Self {
+        Self { matrix: AAMatrix::new(), is_set: [[false; 26]; 26] }
+    }
+
+    /// Set the score for a pair of amino acids.
+    ///
+    /// Scores are symmetric, so this also sets the score for `(b, a)`.
+    pub fn set(&mut self, a: u8, b: u8, score: i8) -> &mut Self {
+        let a = a.to_ascii_uppercase();
+        let b = b.to_ascii_uppercase();
+        assert!(a.is_ascii_uppercase() && b.is_ascii_uppercase(), "Amino acids must be A to Z!");
+        self.matrix.set(a, b, score);
+        self.is_set[(a - b'A') as usize][(b - b'A') as usize] = true;
+        self.is_set[(b - b'A') as usize][(a - b'A') as usize] = true;
+        self
+    }
+
+    /// Build the matrix.
+    ///
+    /// Returns `None` if the score of any pair of standard amino acids is not set.
+    pub fn build(&self) -> Option<AAMatrix> {
+        for &a in Self::STANDARD {
+            for &b in Self::STANDARD {
+                if !self.is_set[(a - b'A') as usize][(b - b'A') as usize] {
+                    return None;
+                }
+            }
+        }
+
+        let represents = |c: u8| -> &[u8] {
+            match c {
+                b'B' => b"DN",
+                b'Z' => b"EQ",
+                b'J' => b"IL",
+                b'X' => Self::STANDARD,
+                _ => &[]
+            }
+        };
+
+        let mut res = self.matrix.clone();
+        for a in b'A'..=b'Z' {
+            for b in b'A'..=b'Z' {
+                if self.is_set[(a - b'A') as usize][(b - b'A') as usize] {
+                    continue;
+                }
+                let a_set = if Self::STANDARD.contains(&a) { core::slice::from_ref(&a) } else { represents(a) };
+                let b_set = if Self::STANDARD.contains(&b) { core::slice::from_ref(&b) } else { represents(b) };
+                let min = a_set
+                    .iter()
+                    .flat_map(|&x| b_set.iter().map(move |&y| self.matrix.get(x, y)))
+                    .min();
+                if let Some(min) = min {
+                    res.set(a, b, min);
+                }
+            }
+        }
+
+        Some(res)
+    }
+}
+
+impl Default for AAMatrixBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse an amino acid symbol in a matrix file, with `None` for `*`.
+fn parse_aa(s: &str) -> Result<Option<u8>, ParseError> {
+    match s.as_bytes() {
+        b"*" => Ok(None),
+        &[c] if c.is_ascii_alphabetic() => Ok(Some(c.to_ascii_uppercase())),
+        _ => Err(ParseError::InvalidSymbol(s.to_owned()))
+    }
+}
+
+/// Error when parsing a scoring matrix.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ParseError {
+    /// There is no header line with the amino acids.
+    MissingHeader,
+    /// An amino acid symbol that is not `A` to `Z` or `*`.
+    InvalidSymbol(String),
+    /// A line (1-based) has a score that is not an `i8` or the wrong number of scores.
+    InvalidLine(usize),
+    /// The scores for a pair of amino acids are different depending on the order.
+    Asymmetric(char, char)
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingHeader => write!(f, "missing header line"),
+            ParseError::InvalidSymbol(s) => write!(f, "invalid amino acid symbol: {}", s),
+            ParseError::InvalidLine(i) => write!(f, "invalid scores on line {}", i),
+            ParseError::Asymmetric(a, b) => write!(f, "asymmetric score for {} and {}", a, b)
+        }
+    }
 }
 
+#[cfg(not(feature = "no_std"))]
+impl std::error::Error for ParseError {}
+
 impl Matrix for AAMatrix {
     const NULL: u8 = b'A' + 26u8;
 
@@ -117,6 +361,7 @@ impl Matrix for AAMatrix {
     #[cfg_attr(feature = "simd_avx2", target_feature(enable = "avx2"))]
     #[cfg_attr(feature = "simd_wasm", target_feature(enable = "simd128"))]
     #[cfg_attr(feature = "simd_neon", target_feature(enable = "neon"))]
+    #[cfg_attr(feature = "simd_avx512", target_feature(enable = "avx512bw"))]
     #[inline]
     unsafe fn get_scores(&self, c: u8, v: HalfSimd, _right: bool) -> Simd {
         // efficiently lookup scores for each character in v
@@ -139,6 +384,7 @@ impl Matrix for AAMatrix {
 /// Supports characters `A`, `C`, `G`, `N`, and `T`. Lowercase characters are uppercased.
 ///
 /// If a larger alphabet is needed (for example, with IUPAC characters), use `AAMatrix` instead.
+/// `AAMatrix::new_iupac` creates a matrix for IUPAC nucleotide codes.
 #[repr(C, align(32))]
 #[derive(Clone, PartialEq, Debug)]
 pub struct NucMatrix {
@@ -162,6 +408,26 @@ impl NucMatrix {
         }
         Self { scores }
     }
+
+    /// Create a simple matrix with a certain match and mismatch score, where any pair of
+    /// characters with an `N` has a score of `n_score`.
+    ///
+    /// For example, `n_score` can be set to 0 so `N` scores neutrally, or set to the mismatch
+    /// score. Note that `new_simple` treats `N` like any other character.
+    pub const fn new_with_n(match_score: i8, mismatch_score: i8, n_score: i8) -> Self {
+        let mut res = Self::new_simple(match_score, mismatch_score);
+        let alpha = *b"ATCGN";
+        let n = b'N';
+        let mut i = 0;
+        while i < alpha.len() {
+            let idx = ((alpha[i] & 0b111) as usize) * 16 + ((n & 0b1111) as usize);
+            res.scores[idx] = n_score;
+            let idx = ((n & 0b111) as usize) * 16 + ((alpha[i] & 0b1111) as usize);
+            res.scores[idx] = n_score;
+            i += 1;
+        }
+        res
+    }
 }
 
 impl Matrix for NucMatrix {
@@ -196,10 +462,17 @@ impl Matrix for NucMatrix {
         unsafe { self.scores.as_ptr().add((i & 0b111) * 16) }
     }
 
+    #[inline]
+    fn is_valid_char(c: u8) -> bool {
+        // other bytes alias the scores of these bytes
+        matches!(c.to_ascii_uppercase(), b'A' | b'C' | b'G' | b'N' | b'T')
+    }
+
     #[cfg_attr(feature = "simd_sse2", target_feature(enable = "sse2"))]
     #[cfg_attr(feature = "simd_avx2", target_feature(enable = "avx2"))]
     #[cfg_attr(feature = "simd_wasm", target_feature(enable = "simd128"))]
     #[cfg_attr(feature = "simd_neon", target_feature(enable = "neon"))]
+    #[cfg_attr(feature = "simd_avx512", target_feature(enable = "avx512bw"))]
     #[inline]
     unsafe fn get_scores(&self, c: u8, v: HalfSimd, _right: bool) -> Simd {
         // efficiently lookup scores for each character in v
@@ -259,6 +532,7 @@ impl Matrix for ByteMatrix {
     #[cfg_attr(feature = "simd_avx2", target_feature(enable = "avx2"))]
     #[cfg_attr(feature = "simd_wasm", target_feature(enable = "simd128"))]
     #[cfg_attr(feature = "simd_neon", target_feature(enable = "neon"))]
+    #[cfg_attr(feature = "simd_avx512", target_feature(enable = "avx512bw"))]
     #[inline]
     unsafe fn get_scores(&self, c: u8, v: HalfSimd, _right: bool) -> Simd {
         let match_scores = halfsimd_set1_i8(self.match_score);
@@ -270,12 +544,23 @@ impl Matrix for ByteMatrix {
     fn convert_char(c: u8) -> u8 {
         c
     }
+
+    #[inline]
+    fn is_valid_char(_c: u8) -> bool {
+        true
+    }
 }
 
 /// Match = 1, mismatch = -1.
 #[cfg_attr(not(target_arch = "wasm32"), no_mangle)]
 pub static NW1: NucMatrix = NucMatrix::new_simple(1, -1);
 
+/// Match = 1, mismatch = 0.
+///
+/// With this matrix, the score counts the number of matches in the alignment, minus the gap costs.
+#[cfg_attr(not(target_arch = "wasm32"), no_mangle)]
+pub static NUC_IDENTITY: NucMatrix = NucMatrix::new_simple(1, 0);
+
 #[cfg_attr(not(target_arch = "wasm32"), no_mangle)]
 pub static BLOSUM45: AAMatrix = AAMatrix { scores: include!("../matrices/BLOSUM45") };
 
@@ -306,6 +591,12 @@ pub static PAM200: AAMatrix = AAMatrix { scores: include!("../matrices/PAM200")
 #[cfg_attr(not(target_arch = "wasm32"), no_mangle)]
 pub static PAM250: AAMatrix = AAMatrix { scores: include!("../matrices/PAM250") };
 
+/// Match = 1, mismatch = 0.
+///
+/// With this matrix, the score counts the number of matches in the alignment, minus the gap costs.
+#[cfg_attr(not(target_arch = "wasm32"), no_mangle)]
+pub static AA_IDENTITY: AAMatrix = AAMatrix::new_simple(1, 0);
+
 /// Match = 1, mismatch = -1.
 #[cfg_attr(not(target_arch = "wasm32"), no_mangle)]
 pub static BYTES1: ByteMatrix = ByteMatrix::new_simple(1, -1);
@@ -330,6 +621,13 @@ pub type GapParams<const GAP_OPEN: i8, const GAP_EXTEND: i8> = Params<{ GAP_OPEN
 ///
 /// Open cost must include the extend cost. For example, with `Gaps { open: -11, extend: -1 }`,
 /// a gap of length 1 costs -11, and a gap of length 2 costs -12.
+///
+/// Use `Gaps::with_convention` to convert gap costs from tools that
+/// do not include the extend cost in the open cost.
+/// Use `Gaps::new` to check that the costs are valid.
+///
+/// Without traceback, `Block::align` also supports linear gap costs where `open` is equal to
+/// `extend`, but traceback and profiles require `open < extend`.
 #[derive(Copy, Clone, PartialEq, Debug)]
 #[repr(C)]
 pub struct Gaps {
@@ -337,6 +635,66 @@ pub struct Gaps {
     pub extend: i8
 }
 
+impl Gaps {
+    /// Create gap costs, checking that `open < extend < 0`.
+    ///
+    /// These are the gap costs that are supported everywhere, including traceback and profiles.
+    pub fn new(open: i8, extend: i8) -> Result<Self, GapError> {
+        if extend >= 0 {
+            return Err(GapError::NonNegativeExtend(extend));
+        }
+        if open >= extend {
+            return Err(GapError::OpenNotLessThanExtend(open, extend));
+        }
+        Ok(Self { open, extend })
+    }
+
+    /// Create gap costs from open and extend costs that follow a certain convention.
+    ///
+    /// The costs are converted so that `Gaps` produces the same gap penalties as
+    /// the specified convention.
+    pub fn with_convention(open: i8, extend: i8, convention: GapConvention) -> Self {
+        match convention {
+            GapConvention::OpenIncludesFirst => Self { open, extend },
+            GapConvention::OpenPlusExtend => Self { open: open.checked_add(extend).expect("Gap open cost is too large!"), extend }
+        }
+    }
+}
+
+/// Error for invalid gap costs.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum GapError {
+    /// The gap extend cost is not negative.
+    NonNegativeExtend(i8),
+    /// The gap open cost is not less than the gap extend cost.
+    OpenNotLessThanExtend(i8, i8)
+}
+
+impl fmt::Display for GapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GapError::NonNegativeExtend(e) => write!(f, "gap extend cost {} must be negative", e),
+            GapError::OpenNotLessThanExtend(o, e) => write!(f, "gap open cost {} must be less than gap extend cost {}", o, e)
+        }
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+impl std::error::Error for GapError {}
+
+/// Different conventions for how gap open and extend costs are combined.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GapConvention {
+    /// A gap of length `n` costs `open + extend * (n - 1)`.
+    ///
+    /// This is the convention used by `Gaps` and parasail.
+    OpenIncludesFirst,
+    /// A gap of length `n` costs `open + extend * n`.
+    ///
+    /// This is the convention used by BLAST and ksw2 (minimap2).
+    OpenPlusExtend
+}
+
 #[allow(non_snake_case)]
 pub trait Profile {
     /// Byte to use as padding.
@@ -404,6 +762,10 @@ pub trait Profile {
     /// When aligning a sequence `q` to a profile `r`, this is the gap open cost at column `i` for
     /// a row transition in the DP matrix with `|q| + 1` rows and `|r| + 1` columns.
     /// This represents starting a gap in `r`.
+    ///
+    /// There is no gap close cost for rows: a row transition stays at the same column `i`, so the
+    /// gap both opens and closes at `i`, and a close cost would be the same as adding it to this
+    /// gap open cost.
     fn set_gap_open_R(&mut self, i: usize, gap: i8);
 
     /// Set the gap open cost for all column transitions.
@@ -451,6 +813,7 @@ pub trait Profile {
 /// Supports characters `A` to `Z`. Lowercase characters are uppercased.
 #[allow(non_snake_case)]
 #[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AAProfile {
     aa_pos: Vec<i16>,
     pos_aa: Vec<i8>,
@@ -592,6 +955,7 @@ impl Profile for AAProfile {
     #[cfg_attr(feature = "simd_avx2", target_feature(enable = "avx2"))]
     #[cfg_attr(feature = "simd_wasm", target_feature(enable = "simd128"))]
     #[cfg_attr(feature = "simd_neon", target_feature(enable = "neon"))]
+    #[cfg_attr(feature = "simd_avx512", target_feature(enable = "avx512bw"))]
     #[inline]
     unsafe fn get_scores_pos(&self, i: usize, v: HalfSimd, _right: bool) -> Simd {
         // efficiently lookup scores for each character in v
@@ -605,6 +969,7 @@ impl Profile for AAProfile {
     #[cfg_attr(feature = "simd_avx2", target_feature(enable = "avx2"))]
     #[cfg_attr(feature = "simd_wasm", target_feature(enable = "simd128"))]
     #[cfg_attr(feature = "simd_neon", target_feature(enable = "neon"))]
+    #[cfg_attr(feature = "simd_avx512", target_feature(enable = "avx512bw"))]
     #[inline]
     unsafe fn get_scores_aa(&self, i: usize, c: u8, _right: bool) -> Simd {
         let matrix_ptr = self.as_ptr_aa(c as usize);
@@ -615,6 +980,7 @@ impl Profile for AAProfile {
     #[cfg_attr(feature = "simd_avx2", target_feature(enable = "avx2"))]
     #[cfg_attr(feature = "simd_wasm", target_feature(enable = "simd128"))]
     #[cfg_attr(feature = "simd_neon", target_feature(enable = "neon"))]
+    #[cfg_attr(feature = "simd_avx512", target_feature(enable = "avx512bw"))]
     #[inline]
     unsafe fn get_gap_open_right_C(&self, i: usize) -> Simd {
         simd_set1_i16(*self.pos_gap_open_C.as_ptr().add(i))
@@ -624,6 +990,7 @@ impl Profile for AAProfile {
     #[cfg_attr(feature = "simd_avx2", target_feature(enable = "avx2"))]
     #[cfg_attr(feature = "simd_wasm", target_feature(enable = "simd128"))]
     #[cfg_attr(feature = "simd_neon", target_feature(enable = "neon"))]
+    #[cfg_attr(feature = "simd_avx512", target_feature(enable = "avx512bw"))]
     #[inline]
     unsafe fn get_gap_close_right_C(&self, i: usize) -> Simd {
         simd_set1_i16(*self.pos_gap_close_C.as_ptr().add(i))
@@ -633,6 +1000,7 @@ impl Profile for AAProfile {
     #[cfg_attr(feature = "simd_avx2", target_feature(enable = "avx2"))]
     #[cfg_attr(feature = "simd_wasm", target_feature(enable = "simd128"))]
     #[cfg_attr(feature = "simd_neon", target_feature(enable = "neon"))]
+    #[cfg_attr(feature = "simd_avx512", target_feature(enable = "avx512bw"))]
     #[inline]
     unsafe fn get_gap_open_right_R(&self, i: usize) -> Simd {
         simd_set1_i16(*self.pos_gap_open_R.as_ptr().add(i))
@@ -642,6 +1010,7 @@ impl Profile for AAProfile {
     #[cfg_attr(feature = "simd_avx2", target_feature(enable = "avx2"))]
     #[cfg_attr(feature = "simd_wasm", target_feature(enable = "simd128"))]
     #[cfg_attr(feature = "simd_neon", target_feature(enable = "neon"))]
+    #[cfg_attr(feature = "simd_avx512", target_feature(enable = "avx512bw"))]
     #[inline]
     unsafe fn get_gap_open_down_C(&self, i: usize) -> Simd {
         simd_loadu(self.pos_gap_open_C.as_ptr().add(i) as *const Simd)
@@ -651,6 +1020,7 @@ impl Profile for AAProfile {
     #[cfg_attr(feature = "simd_avx2", target_feature(enable = "avx2"))]
     #[cfg_attr(feature = "simd_wasm", target_feature(enable = "simd128"))]
     #[cfg_attr(feature = "simd_neon", target_feature(enable = "neon"))]
+    #[cfg_attr(feature = "simd_avx512", target_feature(enable = "avx512bw"))]
     #[inline]
     unsafe fn get_gap_close_down_C(&self, i: usize) -> Simd {
         simd_loadu(self.pos_gap_close_C.as_ptr().add(i) as *const Simd)
@@ -660,6 +1030,7 @@ impl Profile for AAProfile {
     #[cfg_attr(feature = "simd_avx2", target_feature(enable = "avx2"))]
     #[cfg_attr(feature = "simd_wasm", target_feature(enable = "simd128"))]
     #[cfg_attr(feature = "simd_neon", target_feature(enable = "neon"))]
+    #[cfg_attr(feature = "simd_avx512", target_feature(enable = "avx512bw"))]
     #[inline]
     unsafe fn get_gap_open_down_R(&self, i: usize) -> Simd {
         simd_loadu(self.pos_gap_open_R.as_ptr().add(i) as *const Simd)
@@ -674,6 +1045,223 @@ impl Profile for AAProfile {
 }
 
 impl AAProfile {
+    /// Create a profile from a sequence, with scores from a scoring matrix.
+    ///
+    /// This precomputes the scores of every position of the sequence, so it can be reused for
+    /// aligning the same sequence against many other sequences.
+    /// Pass each of the other sequences as the query of `Block::align_profile`. The scores are
+    /// the same as aligning with `Block::align` and `gaps`, but the query and reference are
+    /// swapped in the alignment result and the CIGAR string (`I` and `D` are swapped).
+    ///
+    /// Gap open must cost more than gap extend (`gaps.open < gaps.extend`), since the gap open
+    /// costs of profiles do not include the gap extend cost and must be negative. Linear gap
+    /// costs are not supported.
+    pub fn from_query(b: &[u8], block_size: usize, matrix: &AAMatrix, gaps: Gaps) -> Self {
+        assert!(gaps.open < gaps.extend, "Gap open must cost more than gap extend for profiles!");
+        // gap open costs of profiles do not include the gap extend cost
+        let gap_open = gaps.open - gaps.extend;
+        let mut res = Self::new(b.len(), block_size, gaps.extend);
+
+        for (i, &a) in b.iter().enumerate() {
+            for c in b'A'..=b'Z' {
+                res.set(i + 1, c, matrix.get(a, c));
+            }
+        }
+
+        for i in 0..b.len() + 1 {
+            res.set_gap_open_C(i, gap_open);
+            res.set_gap_close_C(i, 0);
+            res.set_gap_open_R(i, gap_open);
+        }
+
+        res
+    }
+
+    /// Create a profile from a sequence and its Phred quality scores, with scores from a
+    /// scoring matrix that are scaled by the confidence of each position.
+    ///
+    /// The score of every byte at a position is multiplied by `1 - 10^(-q / 10)`, where `q` is
+    /// the quality score at that position (without the ASCII offset of 33), so mismatches at
+    /// low quality positions are penalized less. Any matrix can be used, as long as the sequence
+    /// and the bytes that it is aligned to are in `A` to `Z`.
+    /// See `from_query` and `Block::align_with_quals` for how to align with the profile.
+    /// Like `from_query`, linear gap costs are not supported.
+    pub fn from_query_quals<M: Matrix>(b: &[u8], quals: &[u8], block_size: usize, matrix: &M, gaps: Gaps) -> Self {
+        assert_eq!(b.len(), quals.len(), "Sequence and quality scores must have the same length!");
+        assert!(gaps.open < gaps.extend, "Gap open must cost more than gap extend for profiles!");
+        // gap open costs of profiles do not include the gap extend cost
+        let gap_open = gaps.open - gaps.extend;
+        let mut res = Self::new(b.len(), block_size, gaps.extend);
+
+        for i in 0..b.len() {
+            // f32::powf is not available in core
+            let mut err = 1.0f32;
+            for _ in 0..quals[i] {
+                err *= 0.794_328_2; // 10^(-1 / 10)
+            }
+            let conf = 1.0 - err;
+
+            for c in b'A'..=b'Z' {
+                res.set(i + 1, c, round_i8((matrix.get(b[i], c) as f32) * conf));
+            }
+        }
+
+        for i in 0..b.len() + 1 {
+            res.set_gap_open_C(i, gap_open);
+            res.set_gap_close_C(i, 0);
+            res.set_gap_open_R(i, gap_open);
+        }
+
+        res
+    }
+
+    /// Create a profile from a soft-masked sequence, where lowercase bytes are masked, with
+    /// scores from a scoring matrix and a penalty for matching masked bytes.
+    ///
+    /// Lowercase bytes are scored like uppercase bytes, but `penalty` is added to the score of
+    /// matching the same byte at a masked position, so it should be negative to down-weight
+    /// alignments in repeats. The penalty is applied during alignment, so it affects the
+    /// alignment path and not just the score. Any matrix can be used, as long as the bytes are
+    /// in `A` to `Z` (ignoring case).
+    ///
+    /// This is meant for building a profile from a soft-masked reference. Pass the query to
+    /// `Block::align_profile`, encoded like `AAMatrix` (`PaddedBytes::from_bytes::<AAMatrix>`)
+    /// for any matrix, since the query is looked up in the profile. The query and reference are
+    /// not swapped in the alignment result. Like `from_query`, linear gap costs are not supported.
+    pub fn from_query_softmask<M: Matrix>(b: &[u8], block_size: usize, matrix: &M, gaps: Gaps, penalty: i8) -> Self {
+        assert!(gaps.open < gaps.extend, "Gap open must cost more than gap extend for profiles!");
+        // gap open costs of profiles do not include the gap extend cost
+        let gap_open = gaps.open - gaps.extend;
+        let mut res = Self::new(b.len(), block_size, gaps.extend);
+
+        for (i, &a) in b.iter().enumerate() {
+            let masked = a.is_ascii_lowercase();
+            let a = a.to_ascii_uppercase();
+
+            for c in b'A'..=b'Z' {
+                let score = matrix.get(a, c);
+                res.set(i + 1, c, if masked && c == a { score.saturating_add(penalty) } else { score });
+            }
+        }
+
+        for i in 0..b.len() + 1 {
+            res.set_gap_open_C(i, gap_open);
+            res.set_gap_close_C(i, 0);
+            res.set_gap_open_R(i, gap_open);
+        }
+
+        res
+    }
+
+    /// Background frequencies of the standard amino acids (`ACDEFGHIKLMNPQRSTVWY`), from
+    /// Robinson and Robinson (1991).
+    #[cfg(not(feature = "no_std"))]
+    const BACKGROUND: [f32; 20] = [
+        0.07805, 0.01925, 0.05364, 0.06295, 0.03856, 0.07377, 0.02199, 0.05142, 0.05744, 0.09019,
+        0.02243, 0.04487, 0.05203, 0.04264, 0.05129, 0.07120, 0.05841, 0.06441, 0.01330, 0.03216
+    ];
+
+    /// Create a log-odds profile from the columns of a multiple sequence alignment.
+    ///
+    /// Each element of `columns` contains the residues of every aligned sequence at one column,
+    /// with `-` or `.` for gaps. Bytes that are not letters (like `*`) are also treated as gaps.
+    ///
+    /// The frequency of each of the 20 standard amino acids at a column is computed from its count,
+    /// plus pseudocounts with a total weight of one residue. The pseudocounts are distributed
+    /// according to how likely each amino acid is to be substituted for the observed residues,
+    /// based on the target frequencies implied by `matrix` (assumed to be in half-bits, like
+    /// BLOSUM62). The score of an amino acid is its frequency divided by its background frequency
+    /// (from Robinson and Robinson), in half-bits: `2 * log2(freq / background)`. Columns with no
+    /// standard amino acids have scores of 0. Other letters (like `X`) get the expected score of
+    /// the standard amino acids at the column, weighted by the background frequencies.
+    ///
+    /// Gap open costs from `gaps` are scaled by the fraction of residues (not gaps) in each
+    /// column, so gaps are cheaper to open in gappy columns than in conserved columns.
+    /// Scaled gap open costs are at most -1.
+    ///
+    /// This is not available with `no_std`.
+    #[cfg(not(feature = "no_std"))]
+    pub fn from_msa(columns: &[&[u8]], block_size: usize, matrix: &AAMatrix, gaps: Gaps) -> Self {
+        let standard = AAMatrixBuilder::STANDARD;
+        let bg = &Self::BACKGROUND;
+        let is_gap = |c: u8| !c.is_ascii_alphabetic();
+        // gap open costs of profiles do not include the gap extend cost
+        let gap_open = (gaps.open - gaps.extend) as f32;
+        let mut res = Self::new(columns.len(), block_size, gaps.extend);
+
+        // subst[b][a] is the probability of a given b, which is proportional to the background
+        // frequency of a times the odds ratio 2^(s(a, b) / 2) of the matrix
+        let mut subst = [[0.0f32; 20]; 20];
+        for (&b, row) in standard.iter().zip(subst.iter_mut()) {
+            for ((&a, &p), c) in standard.iter().zip(bg).zip(row.iter_mut()) {
+                *c = p * ((matrix.get(a, b) as f32) / 2.0).exp2();
+            }
+            let sum = row.iter().sum::<f32>();
+            row.iter_mut().for_each(|c| *c /= sum);
+        }
+
+        for (i, col) in columns.iter().enumerate() {
+            let mut counts = [0.0f32; 20];
+            for &c in col.iter() {
+                if let Some(a) = standard.iter().position(|&b| b == c.to_ascii_uppercase()) {
+                    counts[a] += 1.0;
+                }
+            }
+            let n = counts.iter().sum::<f32>();
+
+            let mut scores = [0.0f32; 20];
+            if n > 0.0 {
+                for (a, score) in scores.iter_mut().enumerate() {
+                    let pseudo = counts.iter().zip(&subst).map(|(&c, row)| c * row[a]).sum::<f32>() / n;
+                    let freq = (counts[a] + pseudo) / (n + 1.0);
+                    *score = 2.0 * (freq / bg[a]).log2();
+                }
+            }
+            let expected = scores.iter().zip(bg).map(|(&s, &p)| s * p).sum::<f32>();
+
+            for c in b'A'..=b'Z' {
+                let score = standard.iter().position(|&b| b == c).map(|a| scores[a]).unwrap_or(expected);
+                res.set(i + 1, c, round_i8(score));
+            }
+
+            let residues = col.iter().filter(|&&c| !is_gap(c)).count();
+            let open = if col.is_empty() { gap_open } else { gap_open * (residues as f32) / (col.len() as f32) };
+            let open = round_i8(open).min(-1);
+            res.set_gap_open_C(i + 1, open);
+            res.set_gap_open_R(i + 1, open);
+        }
+
+        let open = round_i8(gap_open).min(-1);
+        res.set_gap_open_C(0, open);
+        res.set_gap_open_R(0, open);
+        for i in 0..columns.len() + 1 {
+            res.set_gap_close_C(i, 0);
+        }
+
+        res
+    }
+
+    /// Add position specific corrections to the scores in the profile.
+    ///
+    /// This can be used to adjust the scores based on the local background amino acid
+    /// frequencies (composition-based statistics), after the scores are set with `set` or `set_all`.
+    ///
+    /// Use `order` to specify the order of bytes that is used in the `corrections` matrix.
+    /// Corrections should be stored in row-major order, where each row is a different position
+    /// and each column is a different byte.
+    /// Corrected scores saturate at the bounds of `i8`.
+    pub fn add_bg_corrections(&mut self, order: &[u8], corrections: &[i8]) {
+        assert!(order.len() <= 32);
+        assert_eq!(corrections.len(), order.len() * self.str_len);
+
+        for i in 1..=self.str_len {
+            for (j, &b) in order.iter().enumerate() {
+                let score = self.get(i, b).saturating_add(corrections[(i - 1) * order.len() + j]);
+                self.set(i, b, score);
+            }
+        }
+    }
+
     fn set_all_core<const REV: bool>(&mut self, order: &[u8], scores: &[i8], left_shift: usize, right_shift: usize) {
         #[repr(align(32))]
         struct A([u8; 32]);
@@ -713,3 +1301,182 @@ impl AAProfile {
         }
     }
 }
+
+// f32::round is not available in core
+fn round_i8(x: f32) -> i8 {
+    let x = if x < 0.0 { x - 0.5 } else { x + 0.5 };
+    x.max(i8::MIN as f32).min(i8::MAX as f32) as i8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp;
+
+    #[test]
+    fn test_from_ncbi_str() {
+        let s = "# comment\n   A  R  *\nA  4 -1 -4\nR -1  5 -4\n* -4 -4  1\n";
+        let m = AAMatrix::from_ncbi_str(s).unwrap();
+        assert_eq!(m.get(b'A', b'A'), 4);
+        assert_eq!(m.get(b'A', b'R'), -1);
+        assert_eq!(m.get(b'R', b'R'), 5);
+        assert_eq!(m.get(b'A', b'C'), i8::MIN);
+
+        assert_eq!(AAMatrix::from_ncbi_str("# only a comment\n"), Err(ParseError::MissingHeader));
+        assert_eq!(AAMatrix::from_ncbi_str("A R\nA 4 -1\nR -2 5\n"), Err(ParseError::Asymmetric('R', 'A')));
+        assert_eq!(AAMatrix::from_ncbi_str("A R\nA 4\nR -1 5\n"), Err(ParseError::InvalidLine(2)));
+        assert_eq!(AAMatrix::from_ncbi_str("A R\nA 4 -1\nR -1 500\n"), Err(ParseError::InvalidLine(3)));
+        assert_eq!(AAMatrix::from_ncbi_str("A 1\n"), Err(ParseError::InvalidSymbol("1".to_owned())));
+    }
+
+    #[test]
+    fn test_new_iupac() {
+        let m = AAMatrix::new_iupac(2, -3);
+        assert_eq!(m.get(b'A', b'A'), 2);
+        assert_eq!(m.get(b'A', b'C'), -3);
+        assert_eq!(m.get(b'R', b'a'), 2);
+        assert_eq!(m.get(b'R', b'Y'), -3);
+        assert_eq!(m.get(b'N', b'B'), 2);
+        assert_eq!(m.get(b'U', b'T'), 2);
+        assert_eq!(m.get(b'X', b'X'), -3);
+    }
+
+    #[test]
+    fn test_with_x_score() {
+        let m = BLOSUM62.with_x_score(0);
+        assert_eq!(m.get(b'X', b'W'), 0);
+        assert_eq!(m.get(b'W', b'X'), 0);
+        assert_eq!(m.get(b'X', b'X'), 0);
+        assert_eq!(m.get(b'A', b'W'), BLOSUM62.get(b'A', b'W'));
+    }
+
+    #[test]
+    fn test_aa_matrix_builder() {
+        let aa = AAMatrixBuilder::STANDARD;
+        let mut builder = AAMatrixBuilder::new();
+        for &a in aa {
+            for &b in aa {
+                builder.set(a, b, BLOSUM62.get(a, b));
+            }
+        }
+        builder.set(b'X', b'X', -1);
+        let m = builder.build().unwrap();
+        assert_eq!(m.get(b'A', b'W'), BLOSUM62.get(b'A', b'W'));
+        assert_eq!(m.get(b'B', b'D'), cmp::min(BLOSUM62.get(b'D', b'D'), BLOSUM62.get(b'N', b'D')));
+        assert_eq!(m.get(b'B', b'Z'), 0);
+        assert_eq!(m.get(b'X', b'X'), -1);
+        assert_eq!(m.get(b'X', b'W'), -4);
+        assert_eq!(m.get(b'O', b'A'), i8::MIN);
+
+        assert!(AAMatrixBuilder::new().set(b'A', b'A', 4).build().is_none());
+    }
+
+    #[test]
+    fn test_nuc_new_with_n() {
+        let m = NucMatrix::new_with_n(2, -3, 0);
+        assert_eq!(m.get(b'A', b'A'), 2);
+        assert_eq!(m.get(b'A', b'T'), -3);
+        assert_eq!(m.get(b'A', b'N'), 0);
+        assert_eq!(m.get(b'N', b'g'), 0);
+        assert_eq!(m.get(b'N', b'N'), 0);
+        assert_eq!(NucMatrix::new_simple(2, -3).get(b'N', b'N'), 2);
+    }
+
+    #[test]
+    fn test_gaps_new() {
+        assert_eq!(Gaps::new(-11, -1), Ok(Gaps { open: -11, extend: -1 }));
+        assert_eq!(Gaps::new(-2, -1), Ok(Gaps { open: -2, extend: -1 }));
+        assert_eq!(Gaps::new(-11, 1), Err(GapError::NonNegativeExtend(1)));
+        assert_eq!(Gaps::new(0, 0), Err(GapError::NonNegativeExtend(0)));
+        assert_eq!(Gaps::new(-11, 0), Err(GapError::NonNegativeExtend(0)));
+        assert_eq!(Gaps::new(-1, -1), Err(GapError::OpenNotLessThanExtend(-1, -1)));
+        assert_eq!(Gaps::new(-1, -11), Err(GapError::OpenNotLessThanExtend(-1, -11)));
+
+        // valid gap costs work with profiles
+        let profile = AAProfile::from_query(b"AAAA", 16, &BLOSUM62, Gaps::new(-2, -1).unwrap());
+        assert_eq!(profile.get(1, b'A'), 4);
+    }
+
+    #[test]
+    fn test_aa_profile_from_query_quals() {
+        let gaps = Gaps { open: -2, extend: -1 };
+        let profile = AAProfile::from_query_quals(b"ACGT", &[40, 10, 3, 0], 32, &NucMatrix::new_simple(4, -6), gaps);
+        assert_eq!(profile.get(1, b'A'), 4);
+        assert_eq!(profile.get(1, b'C'), -6);
+        assert_eq!(profile.get(2, b'C'), 4);
+        assert_eq!(profile.get(2, b'A'), -5);
+        assert_eq!(profile.get(3, b'A'), -3);
+        assert_eq!(profile.get(4, b'A'), 0);
+    }
+
+    #[test]
+    fn test_aa_profile_from_query_softmask() {
+        let gaps = Gaps { open: -2, extend: -1 };
+        let profile = AAProfile::from_query_softmask(b"ACgt", 32, &NucMatrix::new_simple(2, -3), gaps, -1);
+        assert_eq!(profile.get(1, b'A'), 2);
+        assert_eq!(profile.get(2, b'A'), -3);
+        assert_eq!(profile.get(3, b'G'), 1);
+        assert_eq!(profile.get(3, b'A'), -3);
+        assert_eq!(profile.get(4, b'T'), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Gap open must cost more than gap extend for profiles!")]
+    fn test_aa_profile_from_query_linear_gaps() {
+        AAProfile::from_query(b"AAAA", 16, &BLOSUM62, Gaps { open: -1, extend: -1 });
+    }
+
+    #[test]
+    #[should_panic(expected = "Gap open must cost more than gap extend for profiles!")]
+    fn test_aa_profile_from_query_quals_linear_gaps() {
+        let gaps = Gaps { open: -1, extend: -1 };
+        AAProfile::from_query_quals(b"ACGT", &[40; 4], 32, &NucMatrix::new_simple(4, -6), gaps);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no_std"))]
+    fn test_aa_profile_from_msa() {
+        // with a matrix of zeros, pseudocounts follow the background frequencies
+        let zeros = AAMatrix::new_simple(0, 0);
+        let columns: [&[u8]; 2] = [b"AAAW", b"----"];
+        let profile = AAProfile::from_msa(&columns, 32, &zeros, Gaps { open: -11, extend: -1 });
+        assert_eq!(profile.len(), 2);
+        // 2 * log2(((3 + 0.07805) / 5) / 0.07805) = 5.96
+        assert_eq!(profile.get(1, b'A'), 6);
+        // 2 * log2(((1 + 0.0133) / 5) / 0.0133) = 7.86
+        assert_eq!(profile.get(1, b'W'), 8);
+        // 2 * log2(1 / 5) = -4.64
+        assert_eq!(profile.get(1, b'C'), -5);
+        assert_eq!(profile.get(1, b'Y'), -5);
+        // sum of the background frequencies times the scores above = -3.65
+        assert_eq!(profile.get(1, b'X'), -4);
+        assert_eq!(profile.get(2, b'A'), 0);
+        assert_eq!(profile.get(2, b'X'), 0);
+
+        // pseudocounts favor amino acids that are similar to the observed residues
+        let columns: [&[u8]; 3] = [b"IIII", b"WW-W", b"C---"];
+        let profile = AAProfile::from_msa(&columns, 32, &BLOSUM62, Gaps { open: -11, extend: -1 });
+        assert!(profile.get(1, b'I') > profile.get(1, b'V'));
+        assert!(profile.get(1, b'V') > profile.get(1, b'W'));
+        assert!(profile.get(2, b'W') > profile.get(2, b'A'));
+
+        // gaps are cheaper to open in gappy columns
+        assert_eq!(profile.pos_gap_open_C[1], -10);
+        assert_eq!(profile.pos_gap_open_C[2], -8);
+        assert_eq!(profile.pos_gap_open_R[3], -3);
+        assert_eq!(profile.pos_gap_close_C[3], 0);
+
+        // gap open costs are still negative in columns with no residues
+        let columns: [&[u8]; 4] = [b"AAA", b"---", b"RRA", b"A*-"];
+        let profile = AAProfile::from_msa(&columns, 32, &BLOSUM62, Gaps { open: -11, extend: -1 });
+        assert_eq!(profile.pos_gap_open_C[2], -1);
+        assert_eq!(profile.pos_gap_open_R[2], -1);
+        assert_eq!(profile.pos_gap_open_C[4], -3);
+        // bytes that are not letters are not counted
+        assert!(profile.get(4, b'A') < profile.get(1, b'A'));
+
+        let profile = AAProfile::from_msa(&columns, 32, &BLOSUM62, Gaps { open: -1, extend: -1 });
+        assert_eq!(profile.pos_gap_open_C[0], -1);
+        assert_eq!(profile.pos_gap_open_C[1], -1);
+    }
+}
@@ -1,4 +1,4 @@
-use std::arch::wasm32::*;
+use core::arch::wasm32::*;
 
 pub type Simd = v128;
 // no v64 type, so HalfSimd is just v128 with upper half ignored
@@ -66,7 +66,7 @@ macro_rules! simd_extract_i16 {
     ($a:expr, $num:expr) => {
         {
             debug_assert!($num < L);
-            use std::arch::wasm32::*;
+            use core::arch::wasm32::*;
             i16x8_extract_lane::<{ $num }>($a)
         }
     };
@@ -78,7 +78,7 @@ macro_rules! simd_insert_i16 {
     ($a:expr, $v:expr, $num:expr) => {
         {
             debug_assert!($num < L);
-            use std::arch::wasm32::*;
+            use core::arch::wasm32::*;
             i16x8_replace_lane::<{ $num }>($a, $v)
         }
     };
@@ -112,7 +112,7 @@ macro_rules! simd_sl_i16 {
     ($a:expr, $b:expr, $num:expr) => {
         {
             debug_assert!($num <= L);
-            use std::arch::wasm32::*;
+            use core::arch::wasm32::*;
             i16x8_shuffle::<{ 8 - $num }, { 9 - $num }, { 10 - $num }, { 11 - $num }, { 12 - $num }, { 13 - $num }, { 14 - $num }, { 15 - $num }>($b, $a)
         }
     };
@@ -124,7 +124,7 @@ macro_rules! simd_sr_i16 {
     ($a:expr, $b:expr, $num:expr) => {
         {
             debug_assert!($num <= L);
-            use std::arch::wasm32::*;
+            use core::arch::wasm32::*;
             i16x8_shuffle::<{ 0 + $num }, { 1 + $num }, { 2 + $num }, { 3 + $num }, { 4 + $num }, { 5 + $num }, { 6 + $num }, { 7 + $num }>($b, $a)
         }
     };
@@ -179,7 +179,7 @@ macro_rules! simd_prefix_hadd_i16 {
     ($a:expr, $num:expr) => {
         {
             debug_assert!($num <= L);
-            use std::arch::wasm32::*;
+            use core::arch::wasm32::*;
             let mut v = i16x8_sub_sat($a, i16x8_splat(ZERO));
             if $num > 4 {
                 v = i16x8_add_sat(v, simd_sr_i16!(v, v, 4));
@@ -201,7 +201,7 @@ macro_rules! simd_prefix_hmax_i16 {
     ($a:expr, $num:expr) => {
         {
             debug_assert!($num <= L);
-            use std::arch::wasm32::*;
+            use core::arch::wasm32::*;
             let mut v = $a;
             if $num > 4 {
                 v = i16x8_max(v, simd_sr_i16!(v, v, 4));
@@ -223,7 +223,7 @@ macro_rules! simd_suffix_hmax_i16 {
     ($a:expr, $num:expr) => {
         {
             debug_assert!($num <= L);
-            use std::arch::wasm32::*;
+            use core::arch::wasm32::*;
             let mut v = $a;
             if $num > 4 {
                 v = i16x8_max(v, simd_sl_i16!(v, v, 4));
@@ -361,7 +361,7 @@ macro_rules! halfsimd_sr_i8 {
     ($a:expr, $b:expr, $num:expr) => {
         {
             debug_assert!($num <= L);
-            use std::arch::wasm32::*;
+            use core::arch::wasm32::*;
             // special indexing to skip over the high 8 bytes that are unused
             const fn get_idx(i: usize) -> usize { if i >= L { i + L } else { i } }
             i8x16_shuffle::<
@@ -375,6 +375,7 @@ macro_rules! halfsimd_sr_i8 {
 }
 
 #[target_feature(enable = "simd128")]
+#[cfg(not(feature = "no_std"))]
 #[allow(dead_code)]
 pub unsafe fn simd_dbg_i16(v: Simd) {
     #[repr(align(16))]
@@ -390,6 +391,7 @@ pub unsafe fn simd_dbg_i16(v: Simd) {
 }
 
 #[target_feature(enable = "simd128")]
+#[cfg(not(feature = "no_std"))]
 #[allow(dead_code)]
 pub unsafe fn halfsimd_dbg_i8(v: HalfSimd) {
     #[repr(align(16))]
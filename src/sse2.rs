@@ -1,7 +1,7 @@
 #[cfg(target_arch = "x86")]
-use std::arch::x86::*;
+use core::arch::x86::*;
 #[cfg(target_arch = "x86_64")]
-use std::arch::x86_64::*;
+use core::arch::x86_64::*;
 
 pub type Simd = __m128i; // use for storing DP scores
 pub type HalfSimd = __m128i; // used for storing bytes (sequence or scoring matrix)
@@ -66,9 +66,9 @@ macro_rules! simd_extract_i16 {
         {
             debug_assert!($num < L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             _mm_extract_epi16($a, $num as i32) as i16
         }
     };
@@ -81,9 +81,9 @@ macro_rules! simd_insert_i16 {
         {
             debug_assert!($num < L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             _mm_insert_epi16($a, $v as i32, $num as i32)
         }
     };
@@ -100,9 +100,9 @@ macro_rules! simd_sl_i16 {
         {
             debug_assert!($num <= L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             _mm_or_si128(_mm_slli_si128($a, (2 * $num) as i32), _mm_srli_si128($b, ((L - $num) * 2) as i32))
         }
     };
@@ -115,9 +115,9 @@ macro_rules! simd_sr_i16 {
         {
             debug_assert!($num <= L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             _mm_or_si128(_mm_slli_si128($a, ((L - $num) * 2) as i32), _mm_srli_si128($b, (2 * $num) as i32))
         }
     };
@@ -136,9 +136,9 @@ macro_rules! simd_sllz_i16 {
         {
             debug_assert!($num < L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             _mm_slli_si128($a, ($num * 2) as i32)
         }
     };
@@ -181,9 +181,9 @@ macro_rules! simd_prefix_hadd_i16 {
         {
             debug_assert!($num <= L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             let mut v = _mm_subs_epi16($a, _mm_set1_epi16(ZERO));
             if $num > 4 {
                 v = _mm_adds_epi16(v, _mm_srli_si128(v, 8));
@@ -206,9 +206,9 @@ macro_rules! simd_prefix_hmax_i16 {
         {
             debug_assert!($num <= L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             let mut v = $a;
             if $num > 4 {
                 v = _mm_max_epi16(v, _mm_srli_si128(v, 8));
@@ -231,9 +231,9 @@ macro_rules! simd_suffix_hmax_i16 {
         {
             debug_assert!($num <= L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             let mut v = $a;
             if $num > 4 {
                 v = _mm_max_epi16(v, _mm_slli_si128(v, 8));
@@ -434,9 +434,9 @@ macro_rules! halfsimd_sr_i8 {
         {
             debug_assert!($num <= L);
             #[cfg(target_arch = "x86")]
-            use std::arch::x86::*;
+            use core::arch::x86::*;
             #[cfg(target_arch = "x86_64")]
-            use std::arch::x86_64::*;
+            use core::arch::x86_64::*;
             let mask = _mm_srli_si128(_mm_set1_epi32(-1i32), (L + $num) as i32);
             _mm_or_si128(_mm_slli_si128($a, (L - $num) as i32), _mm_and_si128(_mm_srli_si128($b, $num as i32), mask))
         }
@@ -444,6 +444,7 @@ macro_rules! halfsimd_sr_i8 {
 }
 
 #[target_feature(enable = "sse2")]
+#[cfg(not(feature = "no_std"))]
 #[allow(dead_code)]
 pub unsafe fn simd_dbg_i16(v: Simd) {
     #[repr(align(16))]
@@ -459,6 +460,7 @@ pub unsafe fn simd_dbg_i16(v: Simd) {
 }
 
 #[target_feature(enable = "sse2")]
+#[cfg(not(feature = "no_std"))]
 #[allow(dead_code)]
 pub unsafe fn halfsimd_dbg_i8(v: HalfSimd) {
     #[repr(align(16))]
@@ -0,0 +1,60 @@
+//! JavaScript bindings for block aligner, through wasm-bindgen.
+//!
+//! The WASM module is built from the `wasm` directory with wasm-pack.
+
+use wasm_bindgen::prelude::*;
+
+use crate::scan_block::*;
+use crate::scores::*;
+use crate::cigar::*;
+
+/// The score and the CIGAR string of an alignment.
+#[wasm_bindgen]
+pub struct Alignment {
+    score: i32,
+    cigar: String
+}
+
+#[wasm_bindgen]
+impl Alignment {
+    #[wasm_bindgen(getter)]
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cigar(&self) -> String {
+        self.cigar.clone()
+    }
+}
+
+/// Globally align two protein sequences with BLOSUM62, returning an object with the score
+/// and the CIGAR string (with `=` and `X`).
+///
+/// The sequences must only contain uppercase characters `A` to `Z`.
+/// A gap of length n costs `gap_open + gap_extend * (n - 1)`, so both must be negative
+/// and `gap_open` must be less than `gap_extend`.
+/// The min block size is 32, or `max_size` if it is smaller.
+#[wasm_bindgen]
+pub fn align_protein(query: &str, reference: &str, gap_open: i8, gap_extend: i8, max_size: usize) -> Result<Alignment, JsError> {
+    if !query.bytes().chain(reference.bytes()).all(|c| c.is_ascii_uppercase()) {
+        return Err(JsError::new("Sequences must only contain uppercase characters A to Z!"));
+    }
+    if !(gap_open < gap_extend && gap_extend < 0) {
+        return Err(JsError::new("Gap costs must be negative and gap open must cost more than gap extend!"));
+    }
+    if !max_size.is_power_of_two() {
+        return Err(JsError::new("Block size must be a power of two!"));
+    }
+
+    let min_size = max_size.min(32);
+    let q = PaddedBytes::from_str::<AAMatrix>(query, max_size);
+    let r = PaddedBytes::from_str::<AAMatrix>(reference, max_size);
+    let mut a = Block::<true, false>::new(q.len(), r.len(), max_size);
+    a.align(&q, &r, &BLOSUM62, Gaps { open: gap_open, extend: gap_extend }, min_size..=max_size, 0);
+    let res = a.res();
+
+    let mut cigar = Cigar::new(res.query_idx, res.reference_idx);
+    a.trace().cigar_eq(&q, &r, res.query_idx, res.reference_idx, &mut cigar);
+    Ok(Alignment { score: res.score, cigar: cigar.to_string() })
+}